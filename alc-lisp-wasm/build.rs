@@ -50,7 +50,7 @@ fn main() {
         out.write_all(b"\n").expect("to write to file");
     }
 
-    println!("types written to : [{}]", &file_ext_dir);
+    println!("types written to : [{}]", file_ext_dir);
 }
 
 pub struct DropMessage<'m> {
@@ -2,7 +2,7 @@ use std::{cell::LazyCell, panic, sync::LazyLock};
 
 use alc_lisp::{
     ast::AST,
-    interpreter::{builtins::add_generic_builtins, Env, Program},
+    interpreter::{builtins::add_generic_builtins, prelude::add_prelude, Env, Program},
     lexer::Lexer,
 };
 
@@ -72,11 +72,19 @@ pub fn get_ast_gloo(code: String, callback: js_sys::Function) {
         .expect("error running callback");
 }
 
+/// Returns a seed for the random number generator derived from the page timer and `Math.random`
+fn page_seed() -> u64 {
+    let now = PERFORMANCE.with(|p| p.now());
+
+    now.to_bits() ^ (js_sys::Math::random() * u64::MAX as f64) as u64
+}
+
 static WASM_ENV: LazyLock<Env> = LazyLock::new(|| {
     let mut globals: Env = Env::default();
 
     add_generic_builtins(&mut globals);
     add_wasm_builtins(&mut globals);
+    add_prelude(&mut globals).expect("the prelude should run");
 
     globals
 });
@@ -101,6 +109,7 @@ pub fn parse_and_run(code: String, callback: js_sys::Function) {
     let globals: Env = WASM_ENV.clone();
 
     let mut program = Program::new(globals);
+    program.set_seed(page_seed());
 
     let result = program.eval(&root).expect("program::eval");
 
@@ -141,6 +150,7 @@ pub fn run(code: String) {
     let globals: Env = WASM_ENV.clone();
 
     let mut program = Program::new(globals);
+    program.set_seed(page_seed());
 
     let start = PERFORMANCE.with(|p| p.now()); // TODO:Remove Timing code
     let result = program.eval(&root).expect("program::eval");
@@ -8,8 +8,12 @@ macro_rules! ast {
                 token_type: crate::lexer::TokenType::Word,
                 ..Default::default()
             },
-            arguments: vec![$(ast! { word $args}),*],
-            body:Box::new($(ast! $body,)*)
+            doc: None,
+            clauses: vec![crate::ast::FunctionClause {
+                arguments: vec![$(ast! { word $args}),*],
+                parameters: crate::ast::Parameters::from_nodes(&[$(ast! { word $args}),*]).unwrap(),
+                body: Box::new($(ast! $body,)*)
+            }],
         }
     };
     {expr [$($node:tt),*]} => {
@@ -76,7 +80,7 @@ macro_rules! ast {
 #[cfg(test)]
 mod tests {
     use crate::{
-        ast::Node,
+        ast::{FunctionClause, Node, Parameters, Pattern},
         lexer::{Token, TokenType},
     };
 
@@ -90,37 +94,44 @@ mod tests {
                 token_type: TokenType::Word,
                 ..Default::default()
             },
-            arguments: vec![Node::Word(Token {
-                value: "hello".into(),
-                token_type: TokenType::Word,
-                ..Default::default()
-            })],
-            body: Box::new(Node::Expression(
-                [
-                    Node::Word(Token {
-                        value: "+".into(),
-                        token_type: TokenType::Word,
-                        ..Default::default()
-                    }),
-                    Node::NumberLiteral {
-                        value: 1,
-                        token: Token {
-                            value: "1".into(),
-                            token_type: TokenType::NumberLiteral,
+            doc: None,
+            clauses: vec![FunctionClause {
+                arguments: vec![Node::Word(Token {
+                    value: "hello".into(),
+                    token_type: TokenType::Word,
+                    ..Default::default()
+                })],
+                parameters: Parameters {
+                    required: vec![Pattern::Word("hello".into())],
+                    ..Default::default()
+                },
+                body: Box::new(Node::Expression(
+                    [
+                        Node::Word(Token {
+                            value: "+".into(),
+                            token_type: TokenType::Word,
                             ..Default::default()
+                        }),
+                        Node::NumberLiteral {
+                            value: 1,
+                            token: Token {
+                                value: "1".into(),
+                                token_type: TokenType::NumberLiteral,
+                                ..Default::default()
+                            },
                         },
-                    },
-                    Node::NumberLiteral {
-                        value: 2,
-                        token: Token {
-                            value: "2".into(),
-                            token_type: TokenType::NumberLiteral,
-                            ..Default::default()
+                        Node::NumberLiteral {
+                            value: 2,
+                            token: Token {
+                                value: "2".into(),
+                                token_type: TokenType::NumberLiteral,
+                                ..Default::default()
+                            },
                         },
-                    },
-                ]
-                .into(),
-            )),
+                    ]
+                    .into(),
+                )),
+            }],
         };
 
         assert_eq!(
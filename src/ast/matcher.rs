@@ -0,0 +1,187 @@
+//! Arms of the `match` special form, compiled when the expression is parsed
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+
+use super::{Node, Pattern, REST_MARKER};
+
+/// Word that starts a match expression: `(match value pattern result ...)`
+pub const MATCH_WORD: &str = "match";
+/// Matches any value without binding it
+const WILDCARD: &str = "_";
+/// Starts a type pattern: `(type number)` or `(type number n)`
+const TYPE_MARKER: &str = "type";
+/// Starts a guarded arm: `(when pattern condition)`
+const GUARD_MARKER: &str = "when";
+
+/// Value written in a pattern, it matches equal values
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS), ts(export))]
+pub enum MatchLiteral {
+    Null,
+    Integer(isize),
+    Float(f64),
+    String(Arc<str>),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS), ts(export))]
+pub enum MatchPattern {
+    /// `_` matches anything
+    Wildcard,
+    /// A word matches anything and binds the value
+    Bind(Arc<str>),
+    /// Numbers, strings, booleans and `null` match equal values
+    Literal(MatchLiteral),
+    /// Matches values of a type, the inner pattern is tested against the same value
+    Type {
+        name: Arc<str>,
+        pattern: Option<Box<MatchPattern>>,
+    },
+    /// Matches lists with the same amount of items, or at least as many with a rest pattern
+    List {
+        items: Vec<MatchPattern>,
+        rest: Option<Box<MatchPattern>>,
+    },
+    /// Matches maps that have all the keys and binds their values
+    Keys(Vec<Arc<str>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS), ts(export))]
+pub struct MatchArm {
+    pub pattern: MatchPattern,
+    /// Evaluated with the bindings of the pattern, the arm is skipped when it is not truthy
+    pub guard: Option<Node>,
+    pub body: Node,
+}
+
+/// Returns true if the nodes are a match expression with a value: `(match value ...)`
+pub fn is_match(nodes: &[Node]) -> bool {
+    nodes.len() >= 2 && is_marked(nodes, MATCH_WORD)
+}
+
+/// Compiles the arms that follow the value of a match expression
+pub fn match_arms(nodes: &[Node]) -> Result<Arc<[MatchArm]>> {
+    if !nodes.len().is_multiple_of(2) {
+        return Err(anyhow!("invalid match: every pattern needs a result"));
+    }
+
+    nodes
+        .chunks(2)
+        .map(|arm| {
+            let (pattern, guard) = match &arm[0] {
+                Node::Expression(guarded) if is_marked(guarded, GUARD_MARKER) => {
+                    let [_, pattern, guard] = guarded.as_ref() else {
+                        return Err(anyhow!(
+                            "invalid match: guards should be written as ({} pattern condition)",
+                            GUARD_MARKER
+                        ));
+                    };
+
+                    (pattern, Some(guard.clone()))
+                }
+                pattern => (pattern, None),
+            };
+
+            Ok(MatchArm {
+                pattern: MatchPattern::from_node(pattern)?,
+                guard,
+                body: arm[1].clone(),
+            })
+        })
+        .collect()
+}
+
+fn is_marked(nodes: &[Node], marker: &str) -> bool {
+    matches!(nodes.first(), Some(Node::Word(token)) if token.value.as_ref() == marker)
+}
+
+impl MatchPattern {
+    fn from_node(node: &Node) -> Result<Self> {
+        match node {
+            Node::Word(token) => match token.value.as_ref() {
+                WILDCARD => Ok(MatchPattern::Wildcard),
+                "null" => Ok(MatchPattern::Literal(MatchLiteral::Null)),
+                REST_MARKER => Err(anyhow!(
+                    "invalid match pattern: {} should be inside a list",
+                    REST_MARKER
+                )),
+                name => Ok(MatchPattern::Bind(name.into())),
+            },
+            Node::NumberLiteral { value, .. } => {
+                Ok(MatchPattern::Literal(MatchLiteral::Integer(*value)))
+            }
+            Node::FloatLiteral { value, .. } => {
+                Ok(MatchPattern::Literal(MatchLiteral::Float(*value)))
+            }
+            Node::StringLiteral(token) => {
+                let len = token.value.len();
+
+                Ok(MatchPattern::Literal(MatchLiteral::String(
+                    token.value[1..(len - 1)].into(),
+                )))
+            }
+            Node::BooleanLiteral(token) => Ok(MatchPattern::Literal(MatchLiteral::Bool(
+                token.value.as_ref() == "true",
+            ))),
+            Node::List(nodes) => {
+                let mut items = Vec::with_capacity(nodes.len());
+                let mut nodes = nodes.iter();
+
+                while let Some(node) = nodes.next() {
+                    if matches!(node, Node::Word(token) if token.value.as_ref() == REST_MARKER) {
+                        let (Some(rest), None) = (nodes.next(), nodes.next()) else {
+                            return Err(anyhow!(
+                                "invalid match pattern: {} should be followed by a single pattern",
+                                REST_MARKER
+                            ));
+                        };
+
+                        return Ok(MatchPattern::List {
+                            items,
+                            rest: Some(Box::new(MatchPattern::from_node(rest)?)),
+                        });
+                    }
+
+                    items.push(MatchPattern::from_node(node)?);
+                }
+
+                Ok(MatchPattern::List { items, rest: None })
+            }
+            Node::Expression(nodes) if Pattern::is_keys(nodes) => nodes[1..]
+                .iter()
+                .map(|node| match node {
+                    Node::Word(token) => Ok(token.value.clone()),
+                    _ => Err(anyhow!(
+                        "invalid match pattern: the keys of a map pattern should be identifiers"
+                    )),
+                })
+                .collect::<Result<_>>()
+                .map(MatchPattern::Keys),
+            Node::Expression(nodes) if is_marked(nodes, TYPE_MARKER) => match &nodes[1..] {
+                [Node::Word(name)] => Ok(MatchPattern::Type {
+                    name: name.value.clone(),
+                    pattern: None,
+                }),
+                [Node::Word(name), pattern] => Ok(MatchPattern::Type {
+                    name: name.value.clone(),
+                    pattern: Some(Box::new(MatchPattern::from_node(pattern)?)),
+                }),
+                _ => Err(anyhow!(
+                    "invalid match pattern: types should be written as ({} name) or ({} name pattern)",
+                    TYPE_MARKER,
+                    TYPE_MARKER
+                )),
+            },
+            node => Err(anyhow!(
+                "invalid match pattern: unexpected {}",
+                node.type_of()
+            )),
+        }
+    }
+}
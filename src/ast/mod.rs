@@ -3,19 +3,34 @@ use anyhow::{anyhow, Context};
 use crate::lexer::{self, Token, TokenPosition, TokenType};
 
 mod macros;
+mod matcher;
 mod node;
+mod parameters;
+mod pattern;
 
+pub use matcher::*;
 pub use node::*;
+pub use parameters::*;
+pub use pattern::*;
 
 #[derive(Clone, Debug)]
 pub struct AST {
     tokens: Vec<Token>,
     current_position: ASTPosition,
-    errors: Vec<ASTPosition>,
+    errors: Vec<ASTError>,
 }
 
 pub type ASTPosition = Vec<usize>;
 
+/// Error found while parsing. The parse goes on and the node with the error is replaced by an invalid node
+#[derive(Clone, Debug)]
+pub struct ASTError {
+    pub position: ASTPosition,
+    /// Line and column of the token where the error was found
+    pub at: TokenPosition,
+    pub message: String,
+}
+
 impl AST {
     pub fn with_tokens(mut tokens: Vec<Token>) -> Self {
         if tokens.is_empty() {
@@ -30,7 +45,7 @@ impl AST {
         }
     }
 
-    pub fn errors(&self) -> &Vec<ASTPosition> {
+    pub fn errors(&self) -> &Vec<ASTError> {
         &self.errors
     }
 
@@ -39,14 +54,24 @@ impl AST {
     }
 
     pub fn print_errors(&self, _root: &Node) {
-        for (idx, position) in self.errors.iter().enumerate() {
+        for (idx, error) in self.errors.iter().enumerate() {
             // TODO: Fixme this is broken for some reason
             // let node = root.node_at(position).unwrap();
-            let node = 1;
-            eprintln!("AST ERROR [{idx}]:{position:?}\n{node:#?}");
+            eprintln!(
+                "AST ERROR [{idx}]:{:?} at {}:{}: {}",
+                error.position, error.at.line, error.at.col, error.message
+            );
         }
     }
 
+    fn push_error(&mut self, position: ASTPosition, at: TokenPosition, message: String) {
+        self.errors.push(ASTError {
+            position,
+            at,
+            message,
+        });
+    }
+
     fn skip_comments(&mut self) -> Option<usize> {
         let mut count = 0;
         while self
@@ -122,7 +147,11 @@ impl AST {
                     ));
                 }
 
-                Node::Expression(nodes.into())
+                if is_match(&nodes) {
+                    self.parse_match(nodes)
+                } else {
+                    Node::Expression(nodes.into())
+                }
             }
             lexer::TokenType::RParen => {
                 return Err(anyhow!(
@@ -172,64 +201,238 @@ impl AST {
                 ))
             }
             lexer::TokenType::StringLiteral => Node::StringLiteral(token),
-            lexer::TokenType::NumberLiteral => Node::NumberLiteral {
-                value: token.value.parse::<isize>()?,
-                token,
+            lexer::TokenType::NumberLiteral => match token.value.parse::<isize>() {
+                Ok(value) => Node::NumberLiteral { value, token },
+                Err(err) => match token.value.parse::<f64>() {
+                    Ok(value) => Node::FloatLiteral { value, token },
+                    Err(_) => return Err(err.into()),
+                },
             },
             lexer::TokenType::Word => match token.value.as_ref() {
                 "fn" => self.parse_function(token)?,
                 "true" | "false" => Node::BooleanLiteral(token),
                 _ => Node::Word(token),
             },
-            lexer::TokenType::Unknown => Node::Invalid(token), //TODO:Error ?
+            lexer::TokenType::Unknown => {
+                self.push_error(
+                    self.current_position.clone(),
+                    token.start,
+                    format!("unknown token {}", token.value),
+                );
+
+                Node::Invalid(token)
+            }
             lexer::TokenType::Comment => self.parse_expression()?, // Skip
             _ => todo!(),
         };
 
-        if let Node::Invalid(_) = node {
-            self.errors.push(self.current_position.clone());
-        }
-
         *(self.current_position.last_mut().unwrap()) += 1;
 
         Ok(node)
     }
 
+    /// Parses `fn [params] body`, or `fn ([params] body [params] body ...)` for a function with several clauses.
+    ///
+    /// A string before the parameters is kept as the documentation of the function.
+    /// Invalid parameters are recorded as an error of the function and it is parsed as an invalid node
     fn parse_function(&mut self, fn_word: Token) -> anyhow::Result<Node> {
-        let arguments = self
+        let position = self.current_position.clone();
+
+        let mut arguments = self
             .parse_expression()
             .context("invalid function arguments:")?;
 
-        let Node::List(words) = arguments else {
-            return Err(anyhow!("invalid function declaration: invalid arguments"));
+        let doc = match arguments {
+            Node::StringLiteral(doc) => {
+                arguments = self
+                    .parse_expression()
+                    .context("invalid function arguments:")?;
+
+                Some(doc)
+            }
+            _ => None,
         };
 
-        if words.iter().any(|node| !matches!(node, Node::Word(_))) {
-            return Err(anyhow!(
-                "invalid function arguments: arguments should only be identifiers"
-            ));
-        }
+        let clauses = match arguments {
+            Node::List(words) => {
+                let body = self.parse_expression().context("invalid function body:")?;
 
-        let mut body = self.parse_expression().context("invalid function body:")?;
-
-        match body {
-            // Node::Expression(ref exps) => {
-            //     if exps.len() != 1 {
-            //         // FIXME: Wrap the function body since its using Program::eval instead of Program::parse_expression.
-            //         // Is this needed?
-            //         body = Node::Expression([body].into())
-            //     }
-            // }
-            Node::Expression(_) => {}
-            _ => {
-                body = Node::Expression([body].into());
+                function_clause(&words, body).map(|clause| vec![clause])
             }
-        }
+            Node::Expression(clauses) if matches!(clauses.first(), Some(Node::List(_))) => {
+                function_clauses(&clauses)
+            }
+            _ => return Err(anyhow!("invalid function declaration: invalid arguments")),
+        };
+
+        let clauses = match clauses {
+            Ok(clauses) => clauses,
+            Err(err) => {
+                self.push_error(position, fn_word.start, err.to_string());
+
+                return Ok(Node::Invalid(fn_word));
+            }
+        };
 
         Ok(Node::FunctionLiteral {
             token: fn_word,
-            arguments: words.to_vec(),
-            body: Box::new(body),
+            doc,
+            clauses,
+        })
+    }
+
+    /// Compiles the arms of `(match value pattern result ...)`.
+    ///
+    /// Invalid arms are recorded as an error of the expression and it is parsed as an invalid node
+    fn parse_match(&mut self, nodes: Vec<Node>) -> Node {
+        let mut nodes = nodes.into_iter();
+
+        let (Some(Node::Word(token)), Some(value)) = (nodes.next(), nodes.next()) else {
+            unreachable!("is_match checks the word and the value");
+        };
+
+        match match_arms(nodes.as_slice()) {
+            Ok(arms) => Node::Match {
+                token,
+                value: Box::new(value),
+                arms,
+            },
+            Err(err) => {
+                self.push_error(self.current_position.clone(), token.start, err.to_string());
+
+                Node::Invalid(token)
+            }
+        }
+    }
+}
+
+/// Returns the clauses of a function with several of them: `([params] body [params] body ...)`
+pub fn function_clauses(clauses: &[Node]) -> anyhow::Result<Vec<FunctionClause>> {
+    if !clauses.len().is_multiple_of(2) {
+        return Err(anyhow!(
+            "invalid function declaration: every argument list needs a body"
+        ));
+    }
+
+    let clauses = clauses
+        .chunks(2)
+        .map(|clause| {
+            let Node::List(words) = &clause[0] else {
+                return Err(anyhow!("invalid function declaration: invalid arguments"));
+            };
+
+            function_clause(words, clause[1].clone())
         })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    for (idx, clause) in clauses.iter().enumerate() {
+        if let Some(other) = clauses[..idx]
+            .iter()
+            .find(|other| other.parameters.overlaps(&clause.parameters))
+        {
+            return Err(anyhow!(
+                "invalid function declaration: the clauses {} and {} accept the same amount of arguments",
+                other.parameters,
+                clause.parameters
+            ));
+        }
+    }
+
+    Ok(clauses)
+}
+
+/// Returns the clause of the parameters and body, checking that the parameters are valid
+pub fn function_clause(words: &[Node], mut body: Node) -> anyhow::Result<FunctionClause> {
+    let parameters = Parameters::from_nodes(words)?;
+
+    match body {
+        // Node::Expression(ref exps) => {
+        //     if exps.len() != 1 {
+        //         // FIXME: Wrap the function body since its using Program::eval instead of Program::parse_expression.
+        //         // Is this needed?
+        //         body = Node::Expression([body].into())
+        //     }
+        // }
+        Node::Expression(_) => {}
+        _ => {
+            body = Node::Expression([body].into());
+        }
+    }
+
+    Ok(FunctionClause {
+        arguments: words.to_vec(),
+        parameters,
+        body: Box::new(body),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::{Lexer, TokenPosition};
+
+    use super::{Node, AST};
+
+    #[test]
+    fn invalid_parameters_are_recorded_with_their_position() {
+        let mut lexer = Lexer::from_string("(def ok 1)\n(def f fn [a & b c] a)".to_owned());
+        lexer.parse().unwrap();
+
+        let mut ast = AST::with_tokens(lexer.tokens());
+        let root = ast.parse().unwrap();
+
+        let Node::Expression(expressions) = &root else {
+            panic!("expected the root expression");
+        };
+        assert_eq!(
+            expressions.len(),
+            2,
+            "the parse should go on after the error"
+        );
+
+        let [error] = ast.errors().as_slice() else {
+            panic!("expected a single error got {:?}", ast.errors());
+        };
+
+        assert_eq!(error.at, TokenPosition { line: 2, col: 8 });
+        assert!(
+            error
+                .message
+                .contains("should be followed by a single argument"),
+            "{}",
+            error.message
+        );
+    }
+
+    #[test]
+    fn overlapping_clauses_are_rejected() {
+        for code in [
+            "(def f fn ([a] 1 [b] 2))",
+            "(def f fn ([& r] 1 [a] 2))",
+            "(def f fn ([a (b 0)] 1 [a b c] 2 [a b] 3))",
+        ] {
+            let mut lexer = Lexer::from_string(code.to_owned());
+            lexer.parse().unwrap();
+
+            let mut ast = AST::with_tokens(lexer.tokens());
+            ast.parse().unwrap();
+
+            let [error] = ast.errors().as_slice() else {
+                panic!("expected a single error for {code} got {:?}", ast.errors());
+            };
+            assert!(
+                error
+                    .message
+                    .contains("accept the same amount of arguments"),
+                "{}",
+                error.message
+            );
+        }
+
+        let mut lexer = Lexer::from_string("(def f fn ([a] 1 [a b] 2 [a b c & r] 3))".to_owned());
+        lexer.parse().unwrap();
+
+        let mut ast = AST::with_tokens(lexer.tokens());
+        ast.parse().unwrap();
+        assert!(ast.errors().is_empty(), "{:?}", ast.errors());
     }
 }
@@ -4,7 +4,7 @@ use anyhow::anyhow;
 
 use crate::lexer::{Token, TokenPosition};
 
-use super::ASTPosition;
+use super::{ASTPosition, MatchArm, Parameters};
 
 #[derive(Clone, Debug)]
 #[cfg_attr(
@@ -23,14 +23,35 @@ pub enum Node {
         value: isize,
         token: Token,
     },
+    FloatLiteral {
+        value: f64,
+        token: Token,
+    },
     BooleanLiteral(Token),
     FunctionLiteral {
         token: Token,
-        arguments: Vec<Node>,
-        body: Box<Node>,
+        doc: Option<Token>,
+        clauses: Vec<FunctionClause>,
+    },
+    /// `(match value pattern result ...)` with its arms compiled by the parser
+    Match {
+        token: Token,
+        value: Box<Node>,
+        arms: Arc<[MatchArm]>,
     },
 }
 
+/// Parameters and body of a function literal, functions with several clauses pick one by the amount of arguments
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS), ts(export))]
+pub struct FunctionClause {
+    pub arguments: Vec<Node>,
+    /// Parameters read from `arguments` when the function is parsed
+    pub parameters: Parameters,
+    pub body: Box<Node>,
+}
+
 impl Node {
     pub fn type_of(&self) -> &str {
         match self {
@@ -39,9 +60,11 @@ impl Node {
             Node::Expression(_) => "expression",
             Node::List(_) => "list",
             Node::StringLiteral(_) => "string",
-            Node::NumberLiteral { token: _, .. } => "number",
+            Node::NumberLiteral { .. } => "number",
+            Node::FloatLiteral { .. } => "float",
             Node::BooleanLiteral(_) => "boolean",
             Node::FunctionLiteral { .. } => "function",
+            Node::Match { .. } => "match",
         }
     }
 
@@ -78,12 +101,17 @@ impl Node {
             | Node::StringLiteral(token)
             | Node::Word(token)
             | Node::NumberLiteral { token, .. }
+            | Node::FloatLiteral { token, .. }
             | Node::BooleanLiteral(token) => &token.start,
             Node::Expression(vec) | Node::List(vec) => vec
                 .first()
                 .map(|node| node.first_char())
                 .unwrap_or_else(|| &TokenPosition { line: 64, col: 64 }),
-            Node::FunctionLiteral { body, .. } => body.first_char(),
+            Node::FunctionLiteral { token, clauses, .. } => clauses
+                .first()
+                .map(|clause| clause.body.first_char())
+                .unwrap_or(&token.start),
+            Node::Match { token, .. } => &token.start,
         }
     }
 
@@ -93,12 +121,20 @@ impl Node {
             | Node::StringLiteral(token)
             | Node::Word(token)
             | Node::NumberLiteral { token, .. }
+            | Node::FloatLiteral { token, .. }
             | Node::BooleanLiteral(token) => &token.end,
             Node::Expression(vec) | Node::List(vec) => vec
                 .last()
                 .map(|node| node.last_char())
                 .unwrap_or_else(|| &TokenPosition { line: 10, col: 0 }),
-            Node::FunctionLiteral { body, .. } => body.last_char(),
+            Node::FunctionLiteral { token, clauses, .. } => clauses
+                .last()
+                .map(|clause| clause.body.last_char())
+                .unwrap_or(&token.end),
+            Node::Match { value, arms, .. } => arms
+                .last()
+                .map(|arm| arm.body.last_char())
+                .unwrap_or_else(|| value.last_char()),
         }
     }
 }
@@ -114,19 +150,34 @@ impl PartialEq for Node {
             (Self::NumberLiteral { value: l0, .. }, Self::NumberLiteral { value: r0, .. }) => {
                 l0 == r0
             }
+            (Self::FloatLiteral { value: l0, .. }, Self::FloatLiteral { value: r0, .. }) => {
+                l0 == r0
+            }
             (Self::BooleanLiteral(l0), Self::BooleanLiteral(r0)) => l0 == r0,
             (
                 Self::FunctionLiteral {
                     token: l_token,
-                    arguments: l_arguments,
-                    body: l_body,
+                    doc: l_doc,
+                    clauses: l_clauses,
                 },
                 Self::FunctionLiteral {
                     token: r_token,
-                    arguments: r_arguments,
-                    body: r_body,
+                    doc: r_doc,
+                    clauses: r_clauses,
+                },
+            ) => l_token == r_token && l_doc == r_doc && l_clauses == r_clauses,
+            (
+                Self::Match {
+                    token: l_token,
+                    value: l_value,
+                    arms: l_arms,
+                },
+                Self::Match {
+                    token: r_token,
+                    value: r_value,
+                    arms: r_arms,
                 },
-            ) => l_token == r_token && l_arguments == r_arguments && l_body == r_body,
+            ) => l_token == r_token && l_value == r_value && l_arms == r_arms,
             _ => false,
         }
     }
@@ -0,0 +1,139 @@
+//! Parameter list of a user defined function
+use std::fmt::Display;
+
+use anyhow::anyhow;
+
+use super::{Node, Pattern};
+
+/// Marks the parameter that receives the remaining arguments as a list
+pub const REST_MARKER: &str = "&";
+
+/// Parameter with a default value used when the argument is missing
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS), ts(export))]
+pub struct OptionalParameter {
+    pub pattern: Pattern,
+    pub default: Node,
+}
+
+/// Parameters are written as `[a b (c default) & rest]`:
+/// required patterns first, then optional ones with their default value and last the rest parameter
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS), ts(export))]
+pub struct Parameters {
+    pub required: Vec<Pattern>,
+    pub optional: Vec<OptionalParameter>,
+    pub rest: Option<Pattern>,
+}
+
+impl Parameters {
+    pub fn from_nodes(nodes: &[Node]) -> anyhow::Result<Self> {
+        let mut parameters = Parameters::default();
+        let mut nodes = nodes.iter();
+
+        while let Some(node) = nodes.next() {
+            match node {
+                Node::Word(token) if token.value.as_ref() == REST_MARKER => {
+                    let (Some(rest), None) = (nodes.next(), nodes.next()) else {
+                        return Err(anyhow!(
+                            "invalid function arguments: {} should be followed by a single argument",
+                            REST_MARKER
+                        ));
+                    };
+
+                    parameters.rest = Some(Pattern::from_node(rest)?);
+                }
+                Node::Expression(pair) if !Pattern::is_keys(pair) => {
+                    let [pattern, default] = pair.as_ref() else {
+                        return Err(anyhow!(
+                            "invalid function arguments: optional arguments should be written as (name default)"
+                        ));
+                    };
+
+                    parameters.optional.push(OptionalParameter {
+                        pattern: Pattern::from_node(pattern)?,
+                        default: default.clone(),
+                    });
+                }
+                node => {
+                    let pattern = Pattern::from_node(node)?;
+
+                    if !parameters.optional.is_empty() {
+                        return Err(anyhow!(
+                            "invalid function arguments: {} cannot come after an optional argument",
+                            pattern
+                        ));
+                    }
+
+                    parameters.required.push(pattern);
+                }
+            }
+        }
+
+        Ok(parameters)
+    }
+
+    /// Amount of arguments that have to be passed
+    pub fn min_arity(&self) -> usize {
+        self.required.len()
+    }
+
+    /// Amount of arguments that can be passed, None when there is a rest parameter
+    pub fn max_arity(&self) -> Option<usize> {
+        match self.rest {
+            Some(_) => None,
+            None => Some(self.required.len() + self.optional.len()),
+        }
+    }
+
+    /// Checks if some amount of arguments is accepted by both parameter lists
+    pub fn overlaps(&self, other: &Parameters) -> bool {
+        self.max_arity().is_none_or(|max| other.min_arity() <= max)
+            && other.max_arity().is_none_or(|max| self.min_arity() <= max)
+    }
+
+    pub fn accepts(&self, len: usize) -> bool {
+        len >= self.min_arity() && self.max_arity().is_none_or(|max| len <= max)
+    }
+
+    /// Describes the amount of arguments expected, used in arity errors
+    pub fn arity(&self) -> String {
+        match self.max_arity() {
+            None => format!("at least {}", self.min_arity()),
+            Some(max) if max == self.min_arity() => max.to_string(),
+            Some(max) => format!("{} to {}", self.min_arity(), max),
+        }
+    }
+
+    /// Compact version of `arity` used when printing functions: `2`, `1-2` or `1+`
+    pub fn short_arity(&self) -> String {
+        match self.max_arity() {
+            None => format!("{}+", self.min_arity()),
+            Some(max) if max == self.min_arity() => max.to_string(),
+            Some(max) => format!("{}-{}", self.min_arity(), max),
+        }
+    }
+}
+
+impl Display for Parameters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut words = self
+            .required
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .chain(
+                self.optional
+                    .iter()
+                    .map(|optional| format!("({} ...)", optional.pattern)),
+            )
+            .collect::<Vec<_>>();
+
+        if let Some(rest) = &self.rest {
+            words.push(format!("{} {}", REST_MARKER, rest));
+        }
+
+        write!(f, "[{}]", words.join(" "))
+    }
+}
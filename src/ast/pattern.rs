@@ -0,0 +1,116 @@
+//! Destructuring patterns used by function parameters and binding forms
+use std::{fmt::Display, sync::Arc};
+
+use anyhow::anyhow;
+
+use super::{Node, REST_MARKER};
+
+/// Word that starts a map pattern: `(keys a b)`
+pub const KEYS_MARKER: &str = "keys";
+
+/// Describes how a value is split into names
+///
+/// - `name` binds the whole value
+/// - `[a [b c] & rest]` binds the items of a list, `rest` gets the remaining items
+/// - `(keys a b)` binds the values of the keys "a" and "b" of a map
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS), ts(export))]
+pub enum Pattern {
+    Word(Arc<str>),
+    List {
+        items: Vec<Pattern>,
+        rest: Option<Box<Pattern>>,
+    },
+    Keys(Vec<Arc<str>>),
+}
+
+impl Pattern {
+    pub fn from_node(node: &Node) -> anyhow::Result<Self> {
+        match node {
+            Node::Word(token) if token.value.as_ref() == REST_MARKER => Err(anyhow!(
+                "invalid pattern: {} should be inside a list",
+                REST_MARKER
+            )),
+            Node::Word(token) => Ok(Pattern::Word(token.value.clone())),
+            Node::List(nodes) => {
+                let mut items = Vec::with_capacity(nodes.len());
+                let mut nodes = nodes.iter();
+
+                while let Some(node) = nodes.next() {
+                    if matches!(node, Node::Word(token) if token.value.as_ref() == REST_MARKER) {
+                        let (Some(rest), None) = (nodes.next(), nodes.next()) else {
+                            return Err(anyhow!(
+                                "invalid pattern: {} should be followed by a single pattern",
+                                REST_MARKER
+                            ));
+                        };
+
+                        return Ok(Pattern::List {
+                            items,
+                            rest: Some(Box::new(Pattern::from_node(rest)?)),
+                        });
+                    }
+
+                    items.push(Pattern::from_node(node)?);
+                }
+
+                Ok(Pattern::List { items, rest: None })
+            }
+            Node::Expression(nodes) if Pattern::is_keys(nodes) => nodes[1..]
+                .iter()
+                .map(|node| match node {
+                    Node::Word(token) => Ok(token.value.clone()),
+                    _ => Err(anyhow!(
+                        "invalid pattern: the keys of a map pattern should be identifiers"
+                    )),
+                })
+                .collect::<anyhow::Result<_>>()
+                .map(Pattern::Keys),
+            node => Err(anyhow!(
+                "invalid pattern: expected an identifier, a list or ({} ...) got {}",
+                KEYS_MARKER,
+                node.type_of()
+            )),
+        }
+    }
+
+    /// Returns true if the expression is a map pattern like `(keys a b)`
+    pub fn is_keys(nodes: &[Node]) -> bool {
+        matches!(nodes.first(), Some(Node::Word(token)) if token.value.as_ref() == KEYS_MARKER)
+    }
+
+    /// Returns every name bound by the pattern
+    pub fn names(&self) -> Vec<Arc<str>> {
+        match self {
+            Pattern::Word(name) => vec![name.clone()],
+            Pattern::List { items, rest } => items
+                .iter()
+                .chain(rest.as_deref())
+                .flat_map(Pattern::names)
+                .collect(),
+            Pattern::Keys(keys) => keys.clone(),
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Word(name) => f.write_str(name),
+            Pattern::List { items, rest } => {
+                let mut words = items
+                    .iter()
+                    .map(|item| item.to_string())
+                    .collect::<Vec<_>>();
+
+                if let Some(rest) = rest {
+                    words.push(format!("{} {}", REST_MARKER, rest));
+                }
+
+                write!(f, "[{}]", words.join(" "))
+            }
+            Pattern::Keys(keys) => write!(f, "({} {})", KEYS_MARKER, keys.join(" ")),
+        }
+    }
+}
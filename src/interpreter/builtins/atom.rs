@@ -0,0 +1,163 @@
+//! Builtin functions for mutable atoms
+use crate::interpreter::{
+    objects::{Atom, BuiltinFunction, Object},
+    Env, Reference, ATOM, FUNCTION,
+};
+
+use super::{
+    add_std_builtins, call_function,
+    errors::{new_args_len_error, new_function_error, new_type_error_with_pos},
+    type_check,
+};
+
+pub fn add_atom_builtins(env: &mut Env) {
+    let functions: [(&str, BuiltinFunction); _] = [
+        ("atom", ATOM_NEW),
+        ("deref", DEREF),
+        ("reset!", RESET),
+        ("swap!", SWAP),
+    ];
+
+    add_std_builtins(env, functions);
+}
+
+/// Returns a new atom holding the value
+pub const ATOM_NEW: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("atom", &args, 1);
+    }
+
+    Reference::new(Object::Atom(Atom::new(args[0].clone())))
+};
+
+/// Returns the current value of an atom
+pub const DEREF: BuiltinFunction = |_, args| {
+    type_check!("deref", args, [Object::Atom(_)]);
+
+    let Object::Atom(atom) = args[0].as_ref() else {
+        unreachable!()
+    };
+
+    atom.get()
+};
+
+/// Returns an error if the value is the atom itself, an atom holding itself would never be freed
+fn check_not_itself(name: &str, atom: &Atom, value: &Reference) -> Result<(), Reference> {
+    match value.as_ref() {
+        Object::Atom(inner) if inner.ptr_eq(atom) => {
+            Err(new_function_error(name, "an atom cannot hold itself"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Replaces the value of an atom and returns the new value
+pub const RESET: BuiltinFunction = |_, args| {
+    if args.len() != 2 {
+        return new_args_len_error("reset!", &args, 2);
+    }
+
+    let Object::Atom(atom) = args[0].as_ref() else {
+        return new_type_error_with_pos("reset!", ATOM.type_of(), 0);
+    };
+
+    if let Err(err) = check_not_itself("reset!", atom, &args[1]) {
+        return err;
+    }
+
+    atom.set(args[1].clone())
+};
+
+/// Replaces the value of an atom with the result of calling the function with the current value
+/// followed by the rest of the arguments, returns the new value.
+///
+/// It is an error for the function to change the atom while it is running
+pub const SWAP: BuiltinFunction = |program, args| {
+    if args.len() < 2 {
+        return new_args_len_error("swap!", &args, 2);
+    }
+
+    let Object::Atom(atom) = args[0].as_ref() else {
+        return new_type_error_with_pos("swap!", ATOM.type_of(), 0);
+    };
+
+    if !args[1].is_callable() {
+        return new_type_error_with_pos("swap!", FUNCTION.type_of(), 1);
+    }
+
+    let current = atom.get();
+
+    let call_args = std::iter::once(current.clone())
+        .chain(args[2..].iter().cloned())
+        .collect();
+
+    let value = match call_function(program, "swap!", &args[1], call_args) {
+        Ok(value) => value,
+        Err(err) => return err,
+    };
+
+    if let Err(err) = check_not_itself("swap!", atom, &value) {
+        return err;
+    }
+
+    // programs run on a single thread, so only the function itself can change the atom in between
+    if !atom.compare_and_set(&current, value.clone()) {
+        return new_function_error(
+            "swap!",
+            "the atom was changed while the function was running",
+        );
+    }
+
+    value
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::objects::Object,
+        test::{new_test_program, prepare_code},
+    };
+
+    /// Evaluates the lines in order and returns the error of the last one
+    fn eval_error(lines: &[&str]) -> String {
+        let mut program = new_test_program();
+        let (last, first) = lines.split_last().unwrap();
+
+        for line in first {
+            program
+                .eval(&prepare_code(line.to_string()).unwrap())
+                .unwrap();
+        }
+
+        match program.eval(&prepare_code(last.to_string()).unwrap()) {
+            Ok(result) => match result.as_ref() {
+                Object::Error(err) => err.to_string(),
+                _ => panic!("expected an error got {}", result),
+            },
+            Err(err) => format!("{:?}", err),
+        }
+    }
+
+    #[test]
+    fn swap_fails_when_the_function_changes_the_atom() {
+        let err = eval_error(&[
+            "(def counter (atom 0))",
+            "(swap! counter fn [n] (do ((swap! counter fn [m] (+ m 10)) (+ n 1))))",
+        ]);
+
+        assert!(
+            err.contains("changed while the function was running"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn atoms_cannot_hold_themselves() {
+        let err = eval_error(&["(def cell (atom 0))", "(reset! cell cell)"]);
+        assert!(err.contains("cannot hold itself"), "{}", err);
+
+        let err = eval_error(&["(def cell (atom 0))", "(swap! cell fn [_] (do cell))"]);
+        assert!(err.contains("cannot hold itself"), "{}", err);
+    }
+}
@@ -1,6 +1,8 @@
+use std::fmt::Display;
+
 use crate::interpreter::{objects::Object, Reference};
 
-pub fn new_args_len_error(name: &str, args: &[Reference], expected: usize) -> Reference {
+pub fn new_args_len_error(name: &str, args: &[Reference], expected: impl Display) -> Reference {
     Reference::new(Object::Error(
         format!(
             "Invalid amount of argument to function '{}': expected: {} got: {}",
@@ -56,3 +58,9 @@ pub fn new_type_error_with_pos(name: &str, typename: &str, pos: usize) -> Refere
         .into(),
     ))
 }
+
+pub fn new_function_error(name: &str, err: impl Display) -> Reference {
+    Reference::new(Object::Error(
+        format!("Error in function '{}': {}", name, err).into(),
+    ))
+}
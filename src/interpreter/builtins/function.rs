@@ -0,0 +1,145 @@
+//! Builtin functions that create or call other functions
+use std::{collections::BTreeMap, sync::Arc};
+
+use parking_lot::Mutex;
+
+use crate::interpreter::{
+    objects::{BuiltinFunction, Closure, Object},
+    Env, List, Reference, FUNCTION, LIST, NULL,
+};
+
+use super::{
+    add_std_builtins, call_function,
+    errors::{new_args_len_error, new_type_error_with_pos},
+};
+
+pub fn add_function_builtins(env: &mut Env) {
+    let functions: [(&str, BuiltinFunction); _] = [
+        ("apply", APPLY),
+        ("partial", PARTIAL),
+        ("comp", COMP),
+        ("identity", IDENTITY),
+        ("constantly", CONSTANTLY),
+        ("juxt", JUXT),
+        ("memoize", MEMOIZE),
+        ("doc", DOC),
+    ];
+
+    add_std_builtins(env, functions);
+}
+
+fn new_closure(closure: Closure) -> Reference {
+    Reference::new(Object::Closure(Arc::new(closure)))
+}
+
+/// Returns the arguments as a list of functions or a type error
+fn function_args(name: &str, args: &[Reference]) -> Result<List, Reference> {
+    match args.iter().position(|arg| !arg.is_callable()) {
+        Some(pos) => Err(new_type_error_with_pos(name, FUNCTION.type_of(), pos)),
+        None => Ok(args.iter().cloned().collect()),
+    }
+}
+
+/// Calls a function with the items of a list as its arguments.
+///
+/// Arguments between the function and the list are passed before the items of the list
+pub const APPLY: BuiltinFunction = |program, args| {
+    if args.len() < 2 {
+        return new_args_len_error("apply", &args, 2);
+    }
+
+    if !args[0].is_callable() {
+        return new_type_error_with_pos("apply", FUNCTION.type_of(), 0);
+    }
+
+    let last = args.len() - 1;
+
+    let Object::List(rest) = args[last].as_ref() else {
+        return new_type_error_with_pos("apply", LIST.type_of(), last);
+    };
+
+    let call_args = args[1..last].iter().chain(rest.iter()).cloned().collect();
+
+    call_function(program, "apply", &args[0], call_args).unwrap_or_else(|err| err)
+};
+
+/// Returns a function that calls the function with the rest of the arguments followed by its own
+pub const PARTIAL: BuiltinFunction = |_, args| {
+    if args.is_empty() {
+        return new_args_len_error("partial", &args, 1);
+    }
+
+    if !args[0].is_callable() {
+        return new_type_error_with_pos("partial", FUNCTION.type_of(), 0);
+    }
+
+    new_closure(Closure::Partial {
+        function: args[0].clone(),
+        args: args[1..].iter().cloned().collect(),
+    })
+};
+
+/// Composes functions from right to left: `((comp f g) x)` is `(f (g x))`
+pub const COMP: BuiltinFunction = |_, args| match function_args("comp", &args) {
+    Ok(functions) => new_closure(Closure::Compose(functions)),
+    Err(err) => err,
+};
+
+/// Returns its argument
+pub const IDENTITY: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("identity", &args, 1);
+    }
+
+    args[0].clone()
+};
+
+/// Returns a function that ignores its arguments and always returns the value
+pub const CONSTANTLY: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("constantly", &args, 1);
+    }
+
+    new_closure(Closure::Constantly(args[0].clone()))
+};
+
+/// Returns a function that calls every function with its arguments and returns the results as a list
+pub const JUXT: BuiltinFunction = |_, args| {
+    if args.is_empty() {
+        return new_args_len_error("juxt", &args, 1);
+    }
+
+    match function_args("juxt", &args) {
+        Ok(functions) => new_closure(Closure::Juxt(functions)),
+        Err(err) => err,
+    }
+};
+
+/// Returns a function that remembers the results of the function for each list of arguments
+pub const MEMOIZE: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("memoize", &args, 1);
+    }
+
+    if !args[0].is_callable() {
+        return new_type_error_with_pos("memoize", FUNCTION.type_of(), 0);
+    }
+
+    new_closure(Closure::Memoize {
+        function: args[0].clone(),
+        cache: Mutex::new(BTreeMap::new()),
+    })
+};
+
+/// Returns the documentation string of a function, null when it does not have one
+pub const DOC: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("doc", &args, 1);
+    }
+
+    match args[0].as_ref() {
+        Object::Function { doc: Some(doc), .. } => Reference::new(Object::String(doc.clone())),
+        object if object.is_callable() => NULL.clone(),
+        _ => new_type_error_with_pos("doc", FUNCTION.type_of(), 0),
+    }
+};
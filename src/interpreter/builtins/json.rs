@@ -0,0 +1,125 @@
+//! Builtin functions for parsing and serializing JSON
+use std::sync::Arc;
+
+use serde_json::{Number, Value};
+
+use crate::interpreter::{
+    bool_from_native, is_truthy,
+    objects::{BuiltinFunction, Object},
+    Env, Map, Reference, NULL, STRING,
+};
+
+use super::{
+    add_std_builtins,
+    errors::{new_args_len_error, new_type_error_with_pos},
+};
+
+pub fn add_json_builtins(env: &mut Env) {
+    let functions: [(&str, BuiltinFunction); _] = [
+        ("json_parse", JSON_PARSE),
+        ("json_stringify", JSON_STRINGIFY),
+    ];
+
+    add_std_builtins(env, functions);
+}
+
+/// Converts a JSON value into an object
+pub fn object_from_json(value: Value) -> Reference {
+    match value {
+        Value::Null => NULL.clone(),
+        Value::Bool(value) => bool_from_native(value),
+        Value::Number(number) => {
+            if let Some(value) = number.as_i64().and_then(|v| isize::try_from(v).ok()) {
+                Reference::new(Object::Integer(value))
+            } else {
+                Reference::new(Object::Float(number.as_f64().unwrap_or(f64::NAN)))
+            }
+        }
+        Value::String(value) => Reference::new(Object::String(value.into())),
+        Value::Array(values) => Reference::new(Object::List(
+            values.into_iter().map(object_from_json).collect(),
+        )),
+        Value::Object(values) => Reference::new(Object::Map(Arc::new(
+            values
+                .into_iter()
+                .map(|(key, value)| (key.into(), object_from_json(value)))
+                .collect::<Map>(),
+        ))),
+    }
+}
+
+/// Converts an object into a JSON value. Errors if the object cannot be represented as JSON
+pub fn object_to_json(object: &Object) -> Result<Value, String> {
+    match object {
+        Object::Null => Ok(Value::Null),
+        Object::Bool(value) => Ok(Value::Bool(*value)),
+        Object::Integer(value) => Ok(Value::Number((*value as i64).into())),
+        Object::Float(value) => Number::from_f64(*value)
+            .map(Value::Number)
+            .ok_or_else(|| format!("cannot represent {:?} as JSON", value)),
+        Object::String(value) => Ok(Value::String(value.to_string())),
+        Object::List(values) => values
+            .iter()
+            .map(|value| object_to_json(value))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Object::Map(values) => values
+            .iter()
+            .map(|(key, value)| Ok((key.to_string(), object_to_json(value)?)))
+            .collect::<Result<_, _>>()
+            .map(Value::Object),
+        obj => Err(format!(
+            "cannot represent value of type {} as JSON",
+            obj.type_of()
+        )),
+    }
+}
+
+/// Parses a JSON string into a value
+pub const JSON_PARSE: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 1 {
+        return new_args_len_error("json_parse", &args, 1);
+    }
+
+    let Object::String(input) = args[0].as_ref() else {
+        return new_type_error_with_pos("json_parse", STRING.type_of(), 0);
+    };
+
+    match serde_json::from_str::<Value>(input) {
+        Ok(value) => object_from_json(value),
+        Err(err) => Reference::new(Object::Error(
+            format!("json_parse: invalid JSON: {}", err).into(),
+        )),
+    }
+};
+
+/// Serializes a value into a JSON string
+///
+/// Second argument is optional: if truthy the output is pretty printed
+pub const JSON_STRINGIFY: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 1 && len != 2 {
+        return new_args_len_error("json_stringify", &args, "1 to 2");
+    }
+
+    let pretty = args.get(1).is_some_and(is_truthy);
+
+    let value = match object_to_json(&args[0]) {
+        Ok(value) => value,
+        Err(err) => {
+            return Reference::new(Object::Error(format!("json_stringify: {}", err).into()));
+        }
+    };
+
+    let result = if pretty {
+        serde_json::to_string_pretty(&value)
+    } else {
+        serde_json::to_string(&value)
+    };
+
+    match result {
+        Ok(output) => Reference::new(Object::String(output.into())),
+        Err(err) => Reference::new(Object::Error(format!("json_stringify: {}", err).into())),
+    }
+};
@@ -1,15 +1,21 @@
 //! Builtin functions for working with lists
-use std::sync::Arc;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
 use crate::interpreter::{
-    is_error, is_truthy, map_rust_error,
+    bool_from_native, is_truthy,
     objects::{BuiltinFunction, Object},
-    Env, Reference, FUNCTION, LIST, NULL, NUMBER,
+    Env, List, Map, Program, Reference, FUNCTION, LIST, NULL, NUMBER,
 };
 
 use super::{
-    errors::{new_args_len_error, new_type_error_with_pos},
-    typecheck_args,
+    add_std_builtins, call_function,
+    errors::{new_args_len_error, new_function_error, new_type_error_with_pos},
+    sequence::{lazy_filter, lazy_map, lazy_take},
+    type_check, typecheck_args, unwrap_args,
 };
 
 pub fn add_list_builtins(env: &mut Env) {
@@ -19,6 +25,33 @@ pub fn add_list_builtins(env: &mut Env) {
         ("tail", TAIL),
         ("slice", SLICE),
         ("sort", SORT),
+        ("sort_by", SORT_BY),
+        ("min_by", MIN_BY),
+        ("max_by", MAX_BY),
+        ("reverse", REVERSE),
+        ("range", RANGE),
+        ("enumerate", ENUMERATE),
+        ("take", TAKE),
+        ("drop", DROP),
+        ("take_while", TAKE_WHILE),
+        ("drop_while", DROP_WHILE),
+        ("partition", PARTITION),
+        ("chunk", CHUNK),
+        ("window", WINDOW),
+        ("group_by", GROUP_BY),
+        ("frequencies", FREQUENCIES),
+        ("distinct", DISTINCT),
+        ("find", FIND),
+        ("any?", ANY),
+        ("all?", ALL),
+        ("count", COUNT),
+        ("cons", CONS),
+        ("append", APPEND),
+        ("index_of", INDEX_OF),
+        ("assoc_at", ASSOC_AT),
+        ("update_at", UPDATE_AT),
+        ("insert_at", INSERT_AT),
+        ("remove_at", REMOVE_AT),
         ("flat", FLAT),
         ("reduce", REDUCE),
         ("map", MAP),
@@ -26,92 +59,71 @@ pub fn add_list_builtins(env: &mut Env) {
         ("concat", CONCAT),
     ];
 
-    functions
-        .into_iter()
-        .map(|(name, function)| (name, Reference::new(Object::Builtin { function })))
-        .for_each(|(name, function)| {
-            env.insert(name.into(), function.clone());
-            env.insert(("std/".to_owned() + name).into(), function);
-        });
+    add_std_builtins(env, functions);
 }
 
 /// Maps a function over a list and returns it's results as a new list
+///
+/// Over a sequence it returns a new lazy sequence
 pub const MAP: BuiltinFunction = |program, args| {
     let len = args.len();
     if len != 2 {
         return new_args_len_error("map", &args, 2);
     }
 
+    if let Object::Sequence(sequence) = args[0].as_ref() {
+        return lazy_map(sequence, &args[1]);
+    }
+
     let Object::List(l) = args[0].as_ref() else {
         return new_type_error_with_pos("map", LIST.type_of(), 0);
     };
 
-    match args[1].as_ref() {
-        Object::Function {
-            parameters,
-            body,
-            env,
-            ..
-        } => {
-            let result = l
-                .iter()
-                .map(|item| program.run_function(env, body, parameters, &[item.clone()]))
-                .collect::<anyhow::Result<Arc<_>>>();
-
-            match result {
-                Ok(result) => Reference::new(Object::List(result)),
-                Err(err) => Reference::new(Object::Error(err.to_string().into())),
-            }
-        }
-        Object::Builtin { function } => {
-            let result = l
-                .iter()
-                .map(|item| {
-                    Ok(function(program, vec![item.clone()])).and_then(map_rust_error!("map error"))
-                })
-                .collect::<anyhow::Result<Arc<_>>>();
-
-            match result {
-                Ok(result) => Reference::new(Object::List(result)),
-                Err(err) => Reference::new(Object::Error(err.to_string().into())),
-            }
-        }
-        _ => new_type_error_with_pos("map", FUNCTION.type_of(), 1),
+    if !args[1].is_callable() {
+        return new_type_error_with_pos("map", FUNCTION.type_of(), 1);
+    }
+
+    let result = l
+        .iter()
+        .map(|item| call_function(program, "map", &args[1], vec![item.clone()]))
+        .collect::<Result<List, _>>();
+
+    match result {
+        Ok(result) => Reference::new(Object::List(result)),
+        Err(err) => err,
     }
 };
 
+/// Filters a list keeping the items for which the function returns a truthy value
 ///
+/// Over a sequence it returns a new lazy sequence
 pub const FILTER: BuiltinFunction = |program, args| {
     let len = args.len();
     if len != 2 {
         return new_args_len_error("filter", &args, 2);
     }
 
+    if let Object::Sequence(sequence) = args[0].as_ref() {
+        return lazy_filter(sequence, &args[1]);
+    }
+
     let Object::List(l) = args[0].as_ref() else {
         return new_type_error_with_pos("filter", LIST.type_of(), 0);
     };
 
-    match args[1].as_ref() {
-        Object::Function {
-            parameters,
-            body,
-            env,
-            ..
-        } => {
-            let result = l
-                .iter()
-                .map(|item| program.run_function(env, body, parameters, &[item.clone()]))
-                .zip(l.iter())
-                .filter(|(result, _)| result.as_ref().is_ok_and(is_truthy))
-                .map(|(_, value)| Ok(value.clone()))
-                .collect::<anyhow::Result<Arc<_>>>();
-
-            match result {
-                Ok(result) => Reference::new(Object::List(result)),
-                Err(err) => Reference::new(Object::Error(err.to_string().into())),
-            }
-        }
-        _ => new_type_error_with_pos("filter", FUNCTION.type_of(), 1),
+    if !args[1].is_callable() {
+        return new_type_error_with_pos("filter", FUNCTION.type_of(), 1);
+    }
+
+    match test_items(program, "filter", l, &args[1]) {
+        Ok(tests) => Reference::new(Object::List(
+            l.iter()
+                .zip(tests)
+                .filter(|(_, test)| *test)
+                .map(|(item, _)| item.clone())
+                .collect(),
+        )),
+        Err(err) => err,
     }
 };
 
@@ -163,7 +175,7 @@ pub const TAIL: BuiltinFunction = |_, args| {
         return new_type_error_with_pos("tail", LIST.type_of(), 1);
     };
 
-    let vec = l.iter().skip(1).cloned().collect();
+    let vec = sublist(l, 1, l.len());
 
     Reference::new(Object::List(vec))
 };
@@ -188,31 +200,206 @@ pub const SLICE: BuiltinFunction = |_, args| {
         l.len()
     };
 
-    let vec = l
-        .iter()
-        .skip(*n as usize)
-        .take(end - *n as usize)
-        .cloned()
-        .collect();
-
-    Reference::new(Object::List(vec))
+    Reference::new(Object::List(sublist(l, *n as usize, end)))
 };
 
-pub const SORT: BuiltinFunction = |_, args| {
+/// Checks the list and function arguments of the higher order list functions
+fn list_and_function<'a>(
+    name: &str,
+    args: &'a [Reference],
+) -> Result<(&'a List, &'a Reference), Reference> {
+    if args.len() != 2 {
+        return Err(new_args_len_error(name, args, 2));
+    }
+
+    let Object::List(l) = args[0].as_ref() else {
+        return Err(new_type_error_with_pos(name, LIST.type_of(), 0));
+    };
+
+    if !args[1].is_callable() {
+        return Err(new_type_error_with_pos(name, FUNCTION.type_of(), 1));
+    }
+
+    Ok((l, &args[1]))
+}
+
+/// Sorts a list. The sort is stable: equal items keep their order.
+///
+/// Second argument is optional: a comparator called with two items. It can return a number
+/// (negative, zero or positive like a subtraction) or a boolean telling if the first item goes first
+pub const SORT: BuiltinFunction = |program, args| {
     let len = args.len();
 
-    if len != 1 {
-        return new_args_len_error("sort", &args, 2);
+    if len != 1 && len != 2 {
+        return new_args_len_error("sort", &args, 1);
     }
 
     let Object::List(l) = args[0].as_ref() else {
         return new_type_error_with_pos("sort", LIST.type_of(), 0);
     };
 
-    let mut vec = l.to_vec();
-    vec.sort();
+    let mut vec = l.iter().cloned().collect::<Vec<_>>();
+
+    let Some(comparator) = args.get(1) else {
+        vec.sort();
+        return Reference::new(Object::List(vec.into()));
+    };
+
+    if !comparator.is_callable() {
+        return new_type_error_with_pos("sort", FUNCTION.type_of(), 1);
+    }
+
+    let mut compare = |left: &Reference, right: &Reference| -> Result<Ordering, Reference> {
+        let result = call_function(
+            program,
+            "sort",
+            comparator,
+            vec![left.clone(), right.clone()],
+        )?;
+
+        match result.as_ref() {
+            Object::Integer(value) => Ok(value.cmp(&0)),
+            Object::Float(value) => Ok(value.partial_cmp(&0.0).unwrap_or(Ordering::Equal)),
+            _ if is_truthy(&result) => Ok(Ordering::Less),
+            // a boolean comparator only tells if the left item goes first
+            _ => {
+                let swapped = call_function(
+                    program,
+                    "sort",
+                    comparator,
+                    vec![right.clone(), left.clone()],
+                )?;
+
+                if is_truthy(&swapped) {
+                    Ok(Ordering::Greater)
+                } else {
+                    Ok(Ordering::Equal)
+                }
+            }
+        }
+    };
+
+    match merge_sort(vec, &mut compare) {
+        Ok(vec) => Reference::new(Object::List(vec.into())),
+        Err(err) => err,
+    }
+};
+
+/// Stable merge sort that stops at the first error of the comparator.
+///
+/// Used instead of `slice::sort_by` because user comparators are not guaranteed to be a total order
+fn merge_sort<F>(mut items: Vec<Reference>, compare: &mut F) -> Result<Vec<Reference>, Reference>
+where
+    F: FnMut(&Reference, &Reference) -> Result<Ordering, Reference>,
+{
+    if items.len() <= 1 {
+        return Ok(items);
+    }
+
+    let right = items.split_off(items.len() / 2);
+    let left = merge_sort(items, compare)?;
+    let right = merge_sort(right, compare)?;
+
+    let mut output = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        // taking from the left on ties keeps the sort stable
+        if compare(l, r)? == Ordering::Greater {
+            output.extend(right.next());
+        } else {
+            output.extend(left.next());
+        }
+    }
+
+    output.extend(left);
+    output.extend(right);
 
-    Reference::new(Object::List(vec.into()))
+    Ok(output)
+}
+
+/// Returns the keys of the items of a list
+fn keys_of(
+    program: &mut Program,
+    name: &str,
+    list: &List,
+    function: &Reference,
+) -> Result<Vec<Reference>, Reference> {
+    list.iter()
+        .map(|item| call_function(program, name, function, vec![item.clone()]))
+        .collect()
+}
+
+/// Sorts a list by the result of calling the function with each item.
+///
+/// The function is called once per item and the sort is stable: items with equal keys keep their order
+pub const SORT_BY: BuiltinFunction = |program, args| {
+    let (l, function) = match list_and_function("sort_by", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    let keys = match keys_of(program, "sort_by", l, function) {
+        Ok(keys) => keys,
+        Err(err) => return err,
+    };
+
+    let mut pairs = keys.into_iter().zip(l.iter().cloned()).collect::<Vec<_>>();
+    pairs.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+    Reference::new(Object::List(
+        pairs.into_iter().map(|(_, item)| item).collect(),
+    ))
+};
+
+/// Returns the item of a list with the smallest key. The first one wins when several are equal
+pub const MIN_BY: BuiltinFunction = |program, args| {
+    let (l, function) = match list_and_function("min_by", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    let keys = match keys_of(program, "min_by", l, function) {
+        Ok(keys) => keys,
+        Err(err) => return err,
+    };
+
+    keys.iter()
+        .zip(l.iter())
+        .reduce(|min, pair| if pair.0 < min.0 { pair } else { min })
+        .map_or_else(|| NULL.clone(), |(_, item)| item.clone())
+};
+
+/// Returns the item of a list with the largest key. The first one wins when several are equal
+pub const MAX_BY: BuiltinFunction = |program, args| {
+    let (l, function) = match list_and_function("max_by", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    let keys = match keys_of(program, "max_by", l, function) {
+        Ok(keys) => keys,
+        Err(err) => return err,
+    };
+
+    keys.iter()
+        .zip(l.iter())
+        .reduce(|max, pair| if pair.0 > max.0 { pair } else { max })
+        .map_or_else(|| NULL.clone(), |(_, item)| item.clone())
+};
+
+/// Reverses a list or a string
+pub const REVERSE: BuiltinFunction = |_, args| {
+    type_check!("reverse", args, [Object::List(_) | Object::String(_)]);
+
+    match args[0].as_ref() {
+        Object::List(l) => Reference::new(Object::List(l.iter().rev().cloned().collect())),
+        Object::String(s) => {
+            Reference::new(Object::String(s.chars().rev().collect::<String>().into()))
+        }
+        _ => unreachable!(),
+    }
 };
 
 /// Flattens a list
@@ -233,7 +420,7 @@ pub const FLAT: BuiltinFunction = |program, args| {
             let result = FLAT(program, vec![item.clone()]);
 
             match result.as_ref() {
-                Object::List(list) => output.extend_from_slice(list),
+                Object::List(list) => output.extend(list.iter().cloned()),
                 _ => output.push(result),
             }
         }
@@ -261,62 +448,585 @@ pub const CONCAT: BuiltinFunction = |_, args| {
         return err;
     }
 
-    let l = args
-        .iter()
-        .flat_map(|item| {
-            let Object::List(list) = item.as_ref() else {
-                panic!("This should never happen");
-            };
-            list.iter()
-        })
-        .cloned()
-        .collect::<Arc<[Reference]>>();
+    let l = args.iter().fold(List::new(), |mut output, item| {
+        let Object::List(list) = item.as_ref() else {
+            panic!("This should never happen");
+        };
+
+        output.append(list.clone());
+        output
+    });
 
     Reference::new(Object::List(l))
 };
 
-///Reduces a list
+///Reduces a list or a sequence
 ///
 ///Third argument is optional: initial value for the accumulator
 pub const REDUCE: BuiltinFunction = |program, args| {
     let len = args.len();
     if len != 2 && len != 3 {
-        return new_args_len_error("map", &args, 2);
+        return new_args_len_error("reduce", &args, 2);
     }
 
-    let Object::List(l) = args[0].as_ref() else {
-        return new_type_error_with_pos("map", LIST.type_of(), 0);
-    };
+    if !matches!(args[0].as_ref(), Object::List(_) | Object::Sequence(_)) {
+        return new_type_error_with_pos("reduce", LIST.type_of(), 0);
+    }
 
-    match args[1].as_ref() {
-        Object::Function {
-            parameters,
-            body,
-            env,
-            ..
-        } => {
-            let mut acc = args.get(2).cloned().unwrap_or_else(|| NULL.clone());
+    if !args[1].is_callable() {
+        return new_type_error_with_pos("reduce", FUNCTION.type_of(), 1);
+    }
 
+    let mut acc = args.get(2).cloned().unwrap_or_else(|| NULL.clone());
+
+    match args[0].as_ref() {
+        Object::List(l) => {
             for item in l.iter() {
-                let result =
-                    program.run_function(env, body, parameters, &[acc.clone(), item.clone()]);
-
-                match result {
-                    Ok(v) => {
-                        if is_error(&v) {
-                            return v;
-                        }
-
-                        acc = v
-                    }
-                    Err(err) => {
-                        return Reference::new(Object::Error(err.to_string().into()));
-                    }
+                match call_function(program, "reduce", &args[1], vec![acc, item.clone()]) {
+                    Ok(v) => acc = v,
+                    Err(err) => return err,
                 }
             }
+        }
+        Object::Sequence(sequence) => {
+            // items are read one at a time, a sequence only used here forgets them as it goes
+            let unshared = Arc::strong_count(&args[0]) == 1 && !sequence.is_shared();
+            let mut idx = 0;
+
+            loop {
+                let item = match sequence.get(program, idx) {
+                    Ok(Some(item)) => item,
+                    Ok(None) => break,
+                    Err(err) => return err,
+                };
+
+                idx += 1;
+                if unshared {
+                    sequence.release(idx);
+                }
+
+                match call_function(program, "reduce", &args[1], vec![acc, item]) {
+                    Ok(v) => acc = v,
+                    Err(err) => return err,
+                }
+            }
+        }
+        _ => unreachable!(),
+    }
+
+    acc
+};
+
+/// Returns the list argument at the position or a type error
+fn list_arg<'a>(name: &str, args: &'a [Reference], pos: usize) -> Result<&'a List, Reference> {
+    match args.get(pos).map(|arg| arg.as_ref()) {
+        Some(Object::List(l)) => Ok(l),
+        _ => Err(new_type_error_with_pos(name, LIST.type_of(), pos)),
+    }
+}
+
+/// Returns the list and the non negative count arguments of a function
+fn list_and_count<'a>(name: &str, args: &'a [Reference]) -> Result<(&'a List, usize), Reference> {
+    if args.len() != 2 {
+        return Err(new_args_len_error(name, args, 2));
+    }
+
+    let l = list_arg(name, args, 0)?;
+
+    let Object::Integer(n) = args[1].as_ref() else {
+        return Err(new_type_error_with_pos(name, NUMBER.type_of(), 1));
+    };
+
+    match usize::try_from(*n) {
+        Ok(n) => Ok((l, n)),
+        Err(_) => Err(new_function_error(name, "the count cannot be negative")),
+    }
+}
+
+/// Calls the function with each item and returns if the result is truthy
+fn test_items(
+    program: &mut Program,
+    name: &str,
+    list: &List,
+    function: &Reference,
+) -> Result<Vec<bool>, Reference> {
+    list.iter()
+        .map(|item| {
+            call_function(program, name, function, vec![item.clone()]).map(|v| is_truthy(&v))
+        })
+        .collect()
+}
+
+/// Key used when a value is stored in a map. Strings are used as they are
+fn map_key(value: &Object) -> Arc<str> {
+    match value {
+        Object::String(s) => s.clone(),
+        value => value.to_string().into(),
+    }
+}
+
+/// Returns the items between start (inclusive) and end (exclusive), sharing the structure of the list.
+///
+/// The bounds are clamped to the length of the list
+fn sublist(list: &List, start: usize, end: usize) -> List {
+    let end = end.min(list.len());
+    let start = start.min(end);
+
+    list.skip(start).take(end - start)
+}
+
+fn new_list(items: impl IntoIterator<Item = Reference>) -> Reference {
+    Reference::new(Object::List(items.into_iter().collect()))
+}
+
+/// Most items `range` builds in a list, longer ranges have to be lazy
+const MAX_RANGE_LEN: i128 = 10_000_000;
+
+/// Returns a list of numbers from start (inclusive) to end (exclusive)
+///
+/// Called with one argument the range starts at 0. Third argument is optional: the step between numbers.
+/// Ranges longer than `MAX_RANGE_LEN` are an error
+pub const RANGE: BuiltinFunction = |_, args| {
+    type_check!("range", args, Object::Integer(_));
+
+    let numbers = args
+        .iter()
+        .map(|arg| match arg.as_ref() {
+            Object::Integer(value) => *value,
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+
+    let (start, end, step) = match numbers[..] {
+        [end] => (0, end, 1),
+        [start, end] => (start, end, 1),
+        [start, end, step] => (start, end, step),
+        _ => return new_args_len_error("range", &args, 2),
+    };
+
+    if step == 0 {
+        return new_function_error("range", "the step cannot be zero");
+    }
+
+    let (span, stride) = (end as i128 - start as i128, step as i128);
+    let len = if span.signum() == stride.signum() {
+        (span.abs() + stride.abs() - 1) / stride.abs()
+    } else {
+        0
+    };
+
+    if len > MAX_RANGE_LEN {
+        return new_function_error(
+            "range",
+            format!("the range has {len} items, more than {MAX_RANGE_LEN}: use lazy_range for long sequences"),
+        );
+    }
+
+    let mut output = Vec::with_capacity(len as usize);
+    let mut current = Some(start);
+
+    while let Some(value) = current.filter(|v| if step > 0 { *v < end } else { *v > end }) {
+        output.push(Reference::new(Object::Integer(value)));
+        current = value.checked_add(step);
+    }
 
-            acc
+    Reference::new(Object::List(output.into()))
+};
+
+/// Returns a list of [index item] pairs
+pub const ENUMERATE: BuiltinFunction =
+    |_, args| {
+        type_check!("enumerate", args, [Object::List(_)]);
+        unwrap_args!(args, [Object::List(l)]);
+
+        new_list(l.iter().enumerate().map(|(idx, item)| {
+            new_list([Reference::new(Object::Integer(idx as isize)), item.clone()])
+        }))
+    };
+
+/// Returns the first n items of a list or a lazy sequence with the first n items of a sequence
+pub const TAKE: BuiltinFunction = |_, args| match args.first().map(|arg| arg.as_ref()) {
+    Some(Object::Sequence(sequence)) if args.len() == 2 => lazy_take(sequence, &args[1]),
+    _ => match list_and_count("take", &args) {
+        Ok((l, n)) => Reference::new(Object::List(sublist(l, 0, n))),
+        Err(err) => err,
+    },
+};
+
+/// Returns a list without its first n items
+pub const DROP: BuiltinFunction = |_, args| match list_and_count("drop", &args) {
+    Ok((l, n)) => Reference::new(Object::List(sublist(l, n, l.len()))),
+    Err(err) => err,
+};
+
+/// Returns the items of a list until the function returns a falsy value
+pub const TAKE_WHILE: BuiltinFunction = |program, args| {
+    let (l, function) = match list_and_function("take_while", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    let mut output = Vec::new();
+
+    for item in l {
+        match call_function(program, "take_while", function, vec![item.clone()]) {
+            Ok(result) if is_truthy(&result) => output.push(item.clone()),
+            Ok(_) => break,
+            Err(err) => return err,
+        }
+    }
+
+    Reference::new(Object::List(output.into()))
+};
+
+/// Skips the items of a list until the function returns a falsy value
+pub const DROP_WHILE: BuiltinFunction = |program, args| {
+    let (l, function) = match list_and_function("drop_while", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    for (idx, item) in l.iter().enumerate() {
+        match call_function(program, "drop_while", function, vec![item.clone()]) {
+            Ok(result) if is_truthy(&result) => {}
+            Ok(_) => return Reference::new(Object::List(l.skip(idx))),
+            Err(err) => return err,
+        }
+    }
+
+    new_list([])
+};
+
+/// Splits a list in two: the items for which the function returns a truthy value and the rest
+pub const PARTITION: BuiltinFunction = |program, args| {
+    let (l, function) = match list_and_function("partition", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    let tests = match test_items(program, "partition", l, function) {
+        Ok(tests) => tests,
+        Err(err) => return err,
+    };
+
+    let (matching, rest): (Vec<_>, Vec<_>) = l.iter().zip(tests).partition(|(_, test)| *test);
+
+    new_list([
+        new_list(matching.into_iter().map(|(item, _)| item.clone())),
+        new_list(rest.into_iter().map(|(item, _)| item.clone())),
+    ])
+};
+
+/// Splits a list in lists of n items. The last one can be shorter
+pub const CHUNK: BuiltinFunction = |_, args| match list_and_count("chunk", &args) {
+    Ok((_, 0)) => new_function_error("chunk", "the size cannot be zero"),
+    Ok((l, n)) => new_list(
+        (0..l.len())
+            .step_by(n)
+            .map(|start| Reference::new(Object::List(sublist(l, start, start + n)))),
+    ),
+    Err(err) => err,
+};
+
+/// Returns every sequence of n consecutive items of a list
+pub const WINDOW: BuiltinFunction = |_, args| match list_and_count("window", &args) {
+    Ok((_, 0)) => new_function_error("window", "the size cannot be zero"),
+    Ok((l, n)) => new_list(
+        (0..(l.len() + 1).saturating_sub(n))
+            .map(|start| Reference::new(Object::List(sublist(l, start, start + n)))),
+    ),
+    Err(err) => err,
+};
+
+/// Groups the items of a list in a map by the result of the function.
+///
+/// Keys that are not strings are converted into strings
+pub const GROUP_BY: BuiltinFunction = |program, args| {
+    let (l, function) = match list_and_function("group_by", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    let keys = match keys_of(program, "group_by", l, function) {
+        Ok(keys) => keys,
+        Err(err) => return err,
+    };
+
+    let mut groups = BTreeMap::<Arc<str>, Vec<Reference>>::new();
+
+    for (key, item) in keys.iter().zip(l) {
+        groups.entry(map_key(key)).or_default().push(item.clone());
+    }
+
+    let map = groups
+        .into_iter()
+        .map(|(key, items)| (key, new_list(items)))
+        .collect::<Map>();
+
+    Reference::new(Object::Map(Arc::new(map)))
+};
+
+/// Returns a map with the amount of times each item appears in a list.
+///
+/// Keys that are not strings are converted into strings
+pub const FREQUENCIES: BuiltinFunction = |_, args| {
+    type_check!("frequencies", args, [Object::List(_)]);
+    unwrap_args!(args, [Object::List(l)]);
+
+    let mut counts = BTreeMap::<Arc<str>, isize>::new();
+
+    for item in l.iter() {
+        *counts.entry(map_key(item)).or_default() += 1;
+    }
+
+    let map = counts
+        .into_iter()
+        .map(|(key, count)| (key, Reference::new(Object::Integer(count))))
+        .collect::<Map>();
+
+    Reference::new(Object::Map(Arc::new(map)))
+};
+
+/// Returns a list without repeated items, keeping the first time each one appears
+pub const DISTINCT: BuiltinFunction = |_, args| {
+    type_check!("distinct", args, [Object::List(_)]);
+    unwrap_args!(args, [Object::List(l)]);
+
+    // resources are ordered by their address, so the mutex inside them doesn't change the order
+    #[allow(clippy::mutable_key_type)]
+    let mut seen = BTreeSet::new();
+
+    new_list(l.iter().filter(|item| seen.insert(*item)).cloned())
+};
+
+/// Returns the first item for which the function returns a truthy value or null
+pub const FIND: BuiltinFunction = |program, args| {
+    let (l, function) = match list_and_function("find", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    for item in l {
+        match call_function(program, "find", function, vec![item.clone()]) {
+            Ok(result) if is_truthy(&result) => return item.clone(),
+            Ok(_) => {}
+            Err(err) => return err,
+        }
+    }
+
+    NULL.clone()
+};
+
+/// Returns true if the function returns a truthy value for any item
+pub const ANY: BuiltinFunction = |program, args| {
+    let (l, function) = match list_and_function("any?", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    for item in l {
+        match call_function(program, "any?", function, vec![item.clone()]) {
+            Ok(result) if is_truthy(&result) => return bool_from_native(true),
+            Ok(_) => {}
+            Err(err) => return err,
         }
-        _ => new_type_error_with_pos("map", FUNCTION.type_of(), 1),
     }
+
+    bool_from_native(false)
 };
+
+/// Returns true if the function returns a truthy value for every item
+pub const ALL: BuiltinFunction = |program, args| {
+    let (l, function) = match list_and_function("all?", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    for item in l {
+        match call_function(program, "all?", function, vec![item.clone()]) {
+            Ok(result) if is_truthy(&result) => {}
+            Ok(_) => return bool_from_native(false),
+            Err(err) => return err,
+        }
+    }
+
+    bool_from_native(true)
+};
+
+/// Counts the items for which the function returns a truthy value.
+///
+/// Without a function it returns the length of the list
+pub const COUNT: BuiltinFunction = |program, args| {
+    if args.len() == 1 {
+        return match list_arg("count", &args, 0) {
+            Ok(l) => Reference::new(Object::Integer(l.len() as isize)),
+            Err(err) => err,
+        };
+    }
+
+    let (l, function) = match list_and_function("count", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    match test_items(program, "count", l, function) {
+        Ok(tests) => Reference::new(Object::Integer(
+            tests.into_iter().filter(|test| *test).count() as isize,
+        )),
+        Err(err) => err,
+    }
+};
+
+/// Returns a new list with the item added at the start
+pub const CONS: BuiltinFunction = |_, args| {
+    type_check!("cons", args, [_, Object::List(_)]);
+    let Object::List(l) = args[1].as_ref() else {
+        unreachable!()
+    };
+
+    let mut l = l.clone();
+    l.push_front(args[0].clone());
+
+    Reference::new(Object::List(l))
+};
+
+/// Returns a new list with the item added at the end
+pub const APPEND: BuiltinFunction = |_, args| {
+    type_check!("append", args, [Object::List(_), _]);
+    let Object::List(l) = args[0].as_ref() else {
+        unreachable!()
+    };
+
+    let mut l = l.clone();
+    l.push_back(args[1].clone());
+
+    Reference::new(Object::List(l))
+};
+
+/// Returns the position of the first item equal to the value or null if it's not in the list
+pub const INDEX_OF: BuiltinFunction = |_, args| {
+    type_check!("index_of", args, [Object::List(_), _]);
+    let Object::List(l) = args[0].as_ref() else {
+        unreachable!()
+    };
+
+    l.iter().position(|item| item == &args[1]).map_or_else(
+        || NULL.clone(),
+        |idx| Reference::new(Object::Integer(idx as isize)),
+    )
+};
+
+/// Returns the list and the index arguments of a function. The index can be equal to the length if `allow_end` is set
+fn list_and_index<'a>(
+    name: &str,
+    args: &'a [Reference],
+    expected: usize,
+    allow_end: bool,
+) -> Result<(&'a List, usize), Reference> {
+    if args.len() != expected {
+        return Err(new_args_len_error(name, args, expected));
+    }
+
+    let l = list_arg(name, args, 0)?;
+
+    let Object::Integer(idx) = args[1].as_ref() else {
+        return Err(new_type_error_with_pos(name, NUMBER.type_of(), 1));
+    };
+
+    let len = if allow_end { l.len() + 1 } else { l.len() };
+
+    match usize::try_from(*idx) {
+        Ok(idx) if idx < len => Ok((l, idx)),
+        _ => Err(new_function_error(
+            name,
+            format!(
+                "index {} is out of bounds for a list of length {}",
+                idx,
+                l.len()
+            ),
+        )),
+    }
+}
+
+/// Returns a new list with the item at the index replaced by the value
+pub const ASSOC_AT: BuiltinFunction = |_, args| {
+    let (l, idx) = match list_and_index("assoc_at", &args, 3, false) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    Reference::new(Object::List(l.update(idx, args[2].clone())))
+};
+
+/// Returns a new list with the item at the index replaced by the result of calling the function with it
+pub const UPDATE_AT: BuiltinFunction = |program, args| {
+    let (l, idx) = match list_and_index("update_at", &args, 3, false) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    match call_function(program, "update_at", &args[2], vec![l[idx].clone()]) {
+        Ok(value) => Reference::new(Object::List(l.update(idx, value))),
+        Err(err) => err,
+    }
+};
+
+/// Returns a new list with the value inserted at the index, the index can be the length of the list
+pub const INSERT_AT: BuiltinFunction = |_, args| {
+    let (l, idx) = match list_and_index("insert_at", &args, 3, true) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    let mut l = l.clone();
+    l.insert(idx, args[2].clone());
+
+    Reference::new(Object::List(l))
+};
+
+/// Returns a new list without the item at the index
+pub const REMOVE_AT: BuiltinFunction = |_, args| {
+    let (l, idx) = match list_and_index("remove_at", &args, 2, false) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    let mut l = l.clone();
+    l.remove(idx);
+
+    Reference::new(Object::List(l))
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::objects::Object,
+        test::{new_test_program, prepare_code},
+    };
+
+    fn eval(code: &str) -> String {
+        let mut program = new_test_program();
+
+        match program.eval(&prepare_code(code.to_string()).unwrap()) {
+            Ok(result) => match result.as_ref() {
+                Object::Error(err) => err.to_string(),
+                _ => result.to_string(),
+            },
+            Err(err) => format!("{:?}", err),
+        }
+    }
+
+    #[test]
+    fn long_ranges_point_to_lazy_range() {
+        let err = eval("(range 0 9223372036854775807)");
+        assert!(err.contains("use lazy_range"), "{}", err);
+
+        let err = eval("(range 9223372036854775807 -9223372036854775808 -1)");
+        assert!(err.contains("use lazy_range"), "{}", err);
+
+        assert_eq!(eval("(range 10 0 -3)"), "[10 7 4 1]");
+        assert_eq!(eval("(range 0 10 4)"), "[0 4 8]");
+        assert_eq!(eval("(range 5 0)"), "[]");
+        assert_eq!(eval("(zip [1 2 3] [4 5])"), "[[1 4] [2 5]]");
+    }
+}
@@ -0,0 +1,134 @@
+//! Builtin functions for working with maps
+use std::sync::Arc;
+
+use crate::interpreter::{
+    objects::{BuiltinFunction, Object},
+    Env, Map, Reference, MAP, NULL, STRING,
+};
+
+use super::{
+    add_std_builtins,
+    errors::{new_args_len_error, new_type_error_with_pos},
+    type_check,
+};
+
+pub fn add_map_builtins(env: &mut Env) {
+    let functions: [(&str, BuiltinFunction); _] = [
+        ("hash_map", HASH_MAP),
+        ("get", GET),
+        ("assoc", ASSOC),
+        ("keys", KEYS),
+        ("values", VALUES),
+    ];
+
+    add_std_builtins(env, functions);
+}
+
+/// Inserts the key value pairs into the map
+fn insert_pairs(
+    name: &str,
+    map: &mut Map,
+    pairs: &[Reference],
+    offset: usize,
+) -> Option<Reference> {
+    if !pairs.len().is_multiple_of(2) {
+        return Some(Reference::new(Object::Error(
+            format!(
+                "Invalid amount of argument to function '{}': expected key value pairs",
+                name
+            )
+            .into(),
+        )));
+    }
+
+    for (idx, pair) in pairs.chunks_exact(2).enumerate() {
+        let Object::String(key) = pair[0].as_ref() else {
+            return Some(new_type_error_with_pos(
+                name,
+                STRING.type_of(),
+                offset + idx * 2,
+            ));
+        };
+
+        map.insert(key.clone(), pair[1].clone());
+    }
+
+    None
+}
+
+/// Creates a new map from key value pairs
+pub const HASH_MAP: BuiltinFunction = |_, args| {
+    let mut map = Map::new();
+
+    if let Some(err) = insert_pairs("hash_map", &mut map, &args, 0) {
+        return err;
+    }
+
+    Reference::new(Object::Map(Arc::new(map)))
+};
+
+/// Returns the value of a key in a map. If the third argument is provided it's returned when the key is missing
+pub const GET: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 2 && len != 3 {
+        return new_args_len_error("get", &args, 2);
+    }
+
+    let Object::Map(map) = args[0].as_ref() else {
+        return new_type_error_with_pos("get", MAP.type_of(), 0);
+    };
+    let Object::String(key) = args[1].as_ref() else {
+        return new_type_error_with_pos("get", STRING.type_of(), 1);
+    };
+
+    map.get(key)
+        .cloned()
+        .or_else(|| args.get(2).cloned())
+        .unwrap_or_else(|| NULL.clone())
+};
+
+/// Returns a new map with the key value pairs added into it
+pub const ASSOC: BuiltinFunction = |_, args| {
+    if args.is_empty() {
+        return new_args_len_error("assoc", &args, 3);
+    }
+
+    let Object::Map(map) = args[0].as_ref() else {
+        return new_type_error_with_pos("assoc", MAP.type_of(), 0);
+    };
+
+    let mut map = map.as_ref().clone();
+
+    if let Some(err) = insert_pairs("assoc", &mut map, &args[1..], 1) {
+        return err;
+    }
+
+    Reference::new(Object::Map(Arc::new(map)))
+};
+
+/// Returns the keys of a map as a list
+pub const KEYS: BuiltinFunction = |_, args| {
+    type_check!("keys", args, [Object::Map(_)]);
+
+    let Object::Map(map) = args[0].as_ref() else {
+        unreachable!()
+    };
+
+    let keys = map
+        .keys()
+        .map(|key| Reference::new(Object::String(key.clone())))
+        .collect();
+
+    Reference::new(Object::List(keys))
+};
+
+/// Returns the values of a map as a list
+pub const VALUES: BuiltinFunction = |_, args| {
+    type_check!("values", args, [Object::Map(_)]);
+
+    let Object::Map(map) = args[0].as_ref() else {
+        unreachable!()
+    };
+
+    Reference::new(Object::List(map.values().cloned().collect()))
+};
@@ -1,20 +1,40 @@
 //! Generic builtin functions
+mod atom;
 pub mod errors;
+mod function;
 mod list;
+mod map;
+mod module;
 mod number;
+mod random;
+mod sequence;
 mod string;
 
+#[cfg(feature = "json")]
+pub mod json;
+
 #[cfg(feature = "bin")]
 pub mod native;
+#[cfg(feature = "bin")]
+pub mod time;
+
+use std::cmp::Ordering;
 
-use errors::{new_args_len_error, new_type_error_with_got};
+use atom::add_atom_builtins;
+use errors::{new_args_len_error, new_function_error, new_type_error_with_got};
+use function::add_function_builtins;
 use list::add_list_builtins;
-use number::add_number_builtins;
+use map::add_map_builtins;
+use module::add_module_builtins;
+pub(crate) use number::compare_integer_float;
+use number::{add_number_builtins, compare_numbers};
+use random::add_random_builtins;
+use sequence::add_sequence_builtins;
 use string::add_string_builtins;
 
 use super::{
-    bool_from_native,
-    objects::{BuiltinFunction, Object},
+    bool_from_native, is_error,
+    objects::{BuiltinFunction, Namespace, Object},
     Env, Program, Reference, NULL, TRUE,
 };
 
@@ -40,6 +60,7 @@ macro_rules! unsafe_unwrap_args {
         )*
     };
 }
+#[allow(unused_imports)]
 pub(crate) use unsafe_unwrap_args;
 
 #[allow(unused_macros)]
@@ -53,7 +74,10 @@ macro_rules! type_check {
             }
 
             $(
-                let arg = unsafe { $args.get(${index()}).unwrap_unchecked()};
+                // a missing argument would be read past the end of the arguments
+                let Some(arg) = $args.get(${index()}) else {
+                    return crate::interpreter::builtins::errors::new_args_len_error($name, &$args, count);
+                };
 
                 if !matches!(arg.as_ref(), $ty) {
                     let type_name = crate::interpreter::constants::ALL_TYPES
@@ -82,7 +106,7 @@ macro_rules! type_check {
                     .iter()
                     .filter(|v| matches!(v.as_ref(), $type))
                     .map(|v| v.type_of())
-                    .collect::<Arc<[_]>>()
+                    .collect::<std::sync::Arc<[_]>>()
                     .join(" or ");
 
                 return crate::interpreter::builtins::errors::new_type_error_with_got_and_pos(
@@ -115,11 +139,96 @@ where
     None
 }
 
+/// Calls a function or a builtin with the arguments. Errors are returned as error objects
+pub(crate) fn call_function(
+    program: &mut Program,
+    name: &str,
+    function: &Reference,
+    args: Vec<Reference>,
+) -> Result<Reference, Reference> {
+    if !function.is_callable() {
+        return Err(new_function_error(
+            name,
+            format!("{} is not callable", function.type_of()),
+        ));
+    }
+
+    match program.call(function, args) {
+        Ok(result) if is_error(&result) => Err(result),
+        Ok(result) => Ok(result),
+        Err(err) => Err(new_function_error(name, err)),
+    }
+}
+
+/// Name of the namespace with the builtins and the prelude
+pub const STD_NAMESPACE: &str = "std";
+
+/// Returns the namespace defined with the name in the environment, defining an empty one if there is none
+pub fn namespace(env: &mut Env, name: &str) -> Namespace {
+    if let Some(Object::Namespace(namespace)) = env.get(name).map(|value| value.as_ref()) {
+        return namespace.clone();
+    }
+
+    let namespace = Namespace::new(name);
+    env.insert(
+        name.into(),
+        Reference::new(Object::Namespace(namespace.clone())),
+    );
+
+    namespace
+}
+
+/// Adds the functions to a namespace of the environment, they are called as `namespace/name`.
+///
+/// Embedders use it to register their own builtins, the returned namespace can be passed to [`refer`]
+pub fn add_namespace_builtins<'a>(
+    env: &mut Env,
+    name: &str,
+    functions: impl IntoIterator<Item = (&'a str, BuiltinFunction)>,
+) -> Namespace {
+    let namespace = namespace(env, name);
+
+    for (name, function) in functions {
+        namespace.insert(name, Reference::new(Object::Builtin { function }));
+    }
+
+    namespace
+}
+
+/// Defines the definitions of the namespace without its prefix, like `(refer namespace)`
+pub fn refer(env: &mut Env, namespace: &Namespace) {
+    env.extend(namespace.bindings());
+}
+
+/// Adds the functions to the `std` namespace and defines them without the prefix
+fn add_std_builtins<'a>(
+    env: &mut Env,
+    functions: impl IntoIterator<Item = (&'a str, BuiltinFunction)>,
+) {
+    let std = namespace(env, STD_NAMESPACE);
+
+    for (name, function) in functions {
+        let function = Reference::new(Object::Builtin { function });
+
+        std.insert(name, function.clone());
+        env.insert(name.into(), function);
+    }
+}
+
 /// Adds all builtin functions to the environment
 pub fn add_generic_builtins(env: &mut Env) {
     add_number_builtins(env);
     add_list_builtins(env);
+    add_map_builtins(env);
     add_string_builtins(env);
+    add_random_builtins(env);
+    add_sequence_builtins(env);
+    add_function_builtins(env);
+    add_atom_builtins(env);
+    add_module_builtins(env);
+
+    #[cfg(feature = "json")]
+    json::add_json_builtins(env);
 
     let functions: [(&str, BuiltinFunction); _] = [
         ("type", TYPE_OF),
@@ -128,15 +237,11 @@ pub fn add_generic_builtins(env: &mut Env) {
         ("!=", NOT_EQUALS),
         ("<", LESSER_THAN),
         (">", GREATHER_THAN),
+        ("<=", LESSER_OR_EQUAL),
+        (">=", GREATHER_OR_EQUAL),
     ];
 
-    functions
-        .into_iter()
-        .map(|(name, function)| (name, Reference::new(Object::Builtin { function })))
-        .for_each(|(name, function)| {
-            env.insert(name.into(), function.clone());
-            env.insert(("std/".to_owned() + name).into(), function);
-        });
+    add_std_builtins(env, functions);
 }
 
 /// Returns the length of a list or string
@@ -150,10 +255,11 @@ const LEN: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> Referenc
     }
 };
 
+/// Not equal comparison between values, numbers of any kind are compared by their value
 const NOT_EQUALS: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> Reference {
     let len = args.len();
     if len == 0 {
-        return new_args_len_error("==", &args, 2);
+        return new_args_len_error("!=", &args, 2);
     }
 
     if len == 1 {
@@ -163,7 +269,7 @@ const NOT_EQUALS: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> R
     let mut first = &args[0];
 
     for last in args.iter().skip(1) {
-        let value = first.as_ref() == last.as_ref();
+        let value = values_equal(first, last);
 
         if !value {
             return bool_from_native(true);
@@ -175,7 +281,7 @@ const NOT_EQUALS: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> R
     bool_from_native(false)
 };
 
-/// Equal comparison between values
+/// Equal comparison between values, numbers of any kind are compared by their value
 const EQUALS: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> Reference {
     let len = args.len();
     if len == 0 {
@@ -189,7 +295,7 @@ const EQUALS: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> Refer
     let mut first = &args[0];
 
     for last in args.iter().skip(1) {
-        let value = first.as_ref() == last.as_ref();
+        let value = values_equal(first, last);
 
         if !value {
             return bool_from_native(false);
@@ -201,11 +307,48 @@ const EQUALS: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> Refer
     bool_from_native(true)
 };
 
-/// Lesser than comparison between values
-const LESSER_THAN: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> Reference {
+/// Equality used by `==` and `!=`, it agrees with `<=` and `>=` for mixed integers and floats,
+/// also when they are inside lists and maps
+fn values_equal(left: &Object, right: &Object) -> bool {
+    match (left, right) {
+        (Object::Integer(_) | Object::Float(_), Object::Integer(_) | Object::Float(_)) => {
+            compare_numbers(left, right).is_some_and(Ordering::is_eq)
+        }
+        (Object::List(left), Object::List(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right.iter())
+                    .all(|(l, r)| values_equal(l, r))
+        }
+        (Object::Map(left), Object::Map(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right.iter())
+                    .all(|((lk, lv), (rk, rv))| lk == rk && values_equal(lv, rv))
+        }
+        _ => left == right,
+    }
+}
+
+/// Compares two values of the same type, numbers of any kind can be compared between them
+fn compare_values(left: &Object, right: &Object) -> Option<Ordering> {
+    match (left, right) {
+        (Object::Integer(_) | Object::Float(_), _) => compare_numbers(left, right),
+        (Object::Bool(_), Object::Bool(_))
+        | (Object::String(_), Object::String(_))
+        | (Object::List(_), Object::List(_))
+        | (Object::Map(_), Object::Map(_)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+/// Checks that every pair of consecutive values is ordered as `expected` says
+fn compare_chain(name: &str, args: &[Reference], expected: fn(Ordering) -> bool) -> Reference {
     let len = args.len();
     if len == 0 {
-        return new_args_len_error("<", &args, 2);
+        return new_args_len_error(name, args, 2);
     }
 
     if len == 1 {
@@ -215,10 +358,7 @@ const LESSER_THAN: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| ->
     let mut first = &args[0];
 
     for last in args.iter().skip(1) {
-        let value = match (first.as_ref(), last.as_ref()) {
-            (Object::Integer(l), Object::Integer(r)) => l < r,
-            _ => false,
-        };
+        let value = compare_values(first, last).is_some_and(expected);
 
         if !value {
             return bool_from_native(false);
@@ -228,35 +368,26 @@ const LESSER_THAN: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| ->
     }
 
     bool_from_native(true)
+}
+
+/// Lesser than comparison between values
+const LESSER_THAN: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> Reference {
+    compare_chain("<", &args, Ordering::is_lt)
 };
 
 /// Greater than comparison between values
 const GREATHER_THAN: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> Reference {
-    let len = args.len();
-    if len == 0 {
-        return new_args_len_error(">", &args, 2);
-    }
-
-    if len == 1 {
-        return TRUE.clone();
-    }
-
-    let mut first = &args[0];
-
-    for last in args.iter().skip(1) {
-        let value = match (first.as_ref(), last.as_ref()) {
-            (Object::Integer(l), Object::Integer(r)) => l > r,
-            _ => false,
-        };
-
-        if !value {
-            return bool_from_native(false);
-        }
+    compare_chain(">", &args, Ordering::is_gt)
+};
 
-        first = last;
-    }
+/// Lesser or equal comparison between values
+const LESSER_OR_EQUAL: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> Reference {
+    compare_chain("<=", &args, Ordering::is_le)
+};
 
-    bool_from_native(true)
+/// Greater or equal comparison between values
+const GREATHER_OR_EQUAL: BuiltinFunction = |_: &mut Program, args: Vec<Reference>| -> Reference {
+    compare_chain(">=", &args, Ordering::is_ge)
 };
 
 const TYPE_OF: BuiltinFunction = |_, args| {
@@ -265,3 +396,35 @@ const TYPE_OF: BuiltinFunction = |_, args| {
         |v| Reference::new(Object::String(v.type_of().into())),
     )
 };
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::{
+            objects::{BuiltinFunction, Object},
+            Program, Reference,
+        },
+        native::NATIVE_ENV,
+        test::prepare_code,
+    };
+
+    use super::{add_namespace_builtins, refer};
+
+    const ANSWER: BuiltinFunction = |_, _| Reference::new(Object::Integer(42));
+
+    #[test]
+    fn embedders_can_register_a_namespace() {
+        let mut env = NATIVE_ENV.clone();
+
+        let host = add_namespace_builtins(&mut env, "host", [("answer", ANSWER)]);
+        assert!(!env.contains_key("answer"));
+
+        refer(&mut env, &host);
+
+        let mut program = Program::new(env);
+        let code = prepare_code("[(host/answer) (answer)]".to_owned()).unwrap();
+        let result = program.eval(&code).unwrap();
+
+        assert_eq!(result.to_string(), "[42 42]");
+    }
+}
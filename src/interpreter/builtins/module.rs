@@ -0,0 +1,36 @@
+//! Builtin functions to inspect modules
+use std::path::Path;
+
+use crate::interpreter::{
+    objects::{BuiltinFunction, Object},
+    Env, Reference,
+};
+
+use super::{add_std_builtins, errors::new_function_error, type_check};
+
+pub fn add_module_builtins(env: &mut Env) {
+    let functions: [(&str, BuiltinFunction); _] = [("module_exports", MODULE_EXPORTS)];
+
+    add_std_builtins(env, functions);
+}
+
+/// Returns the sorted names a module exports, the path is resolved like the path of import
+pub const MODULE_EXPORTS: BuiltinFunction = |program, args| {
+    type_check!("module_exports", args, [Object::String(_)]);
+
+    let Object::String(path) = args[0].as_ref() else {
+        unreachable!()
+    };
+
+    match program.find_module(Path::new(path.as_ref())) {
+        Ok(Ok(module)) => Reference::new(Object::List(
+            module
+                .exports
+                .iter()
+                .map(|(name, _)| Reference::new(Object::String(name.clone())))
+                .collect(),
+        )),
+        Ok(Err(err)) => new_function_error("module_exports", err),
+        Err(err) => new_function_error("module_exports", format!("{:#}", err)),
+    }
+};
@@ -1,23 +1,29 @@
 //! Builtin functions to be used in a native environment
 
 use std::{
-    env::current_dir,
-    fs::File,
-    io::{Read, Seek},
+    env::{self, current_dir},
+    fs::{self, File, OpenOptions},
+    io::{stdin, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{self, Path, PathBuf},
+    process::{self, Child, ChildStdout, Command, Stdio},
+    sync::Arc,
     thread,
     time::Duration,
 };
 
-use once_cell::sync::Lazy;
-use parking_lot::Mutex;
-
 use crate::interpreter::{
-    objects::{BuiltinFunction, Object},
-    Env, Reference, NULL, NUMBER, STRING,
+    bool_from_native, is_truthy,
+    objects::{BuiltinFunction, NativeSource, Object, Resource, Sequence, Source},
+    Env, Map, Program, Reference, LIST, MAP, NULL, NUMBER, STRING,
 };
 
 use super::{
-    errors::{new_args_len_error, new_type_error_with_pos},
+    add_std_builtins,
+    errors::{
+        new_args_len_error, new_function_error, new_type_error_with_got_and_pos,
+        new_type_error_with_pos,
+    },
+    time::add_time_builtins,
     typecheck_args,
 };
 
@@ -30,21 +36,45 @@ pub fn add_native_builtins(env: &mut Env) {
         ("pwd", PWD),
         ("open", OPEN),
         ("close", CLOSE),
-        ("file", FILE),
+        ("read_line", READ_LINE),
+        ("lines", LINES),
+        ("read_all", READ_ALL),
+        ("read_stdin", READ_STDIN),
         ("read_file", READ_FILE),
+        ("write", WRITE),
+        ("flush", FLUSH),
+        ("seek", SEEK),
+        ("slurp", SLURP),
+        ("spit", SPIT),
+        ("list_dir", LIST_DIR),
+        ("exists?", EXISTS),
+        ("is_dir?", IS_DIR),
+        ("mkdir", MKDIR),
+        ("remove", REMOVE),
+        ("rename", RENAME),
+        ("copy", COPY),
+        ("file_size", FILE_SIZE),
+        ("glob", GLOB),
+        ("path_join", PATH_JOIN),
+        ("basename", BASENAME),
+        ("dirname", DIRNAME),
+        ("extension", EXTENSION),
+        ("absolute", ABSOLUTE),
         ("sleep", SLEEP),
+        ("getenv", GETENV),
+        ("setenv", SETENV),
+        ("exit", EXIT),
+        ("pid", PID),
+        ("exec", EXEC),
+        ("spawn", SPAWN),
+        ("wait", WAIT),
+        ("kill", KILL),
     ];
 
-    functions
-        .into_iter()
-        .map(|(name, function)| (name, Reference::new(Object::Builtin { function })))
-        .for_each(|(name, function)| {
-            env.insert(name.into(), function.clone());
-            env.insert(("std/".to_owned() + name).into(), function);
-        });
-}
+    add_std_builtins(env, functions);
 
-type FileRef = Option<File>;
+    add_time_builtins(env);
+}
 
 pub const SLEEP: BuiltinFunction = |_, args| {
     let Object::Integer(millis) = args[0].as_ref() else {
@@ -55,7 +85,89 @@ pub const SLEEP: BuiltinFunction = |_, args| {
     NULL.clone()
 };
 
-static OPEN_FILE: Lazy<Mutex<FileRef>> = Lazy::new(|| Mutex::new(None));
+/// Returns the value of an environment variable. null if the variable is not set
+pub const GETENV: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 1 {
+        return new_args_len_error("getenv", &args, 1);
+    }
+
+    let Object::String(name) = args[0].as_ref() else {
+        return new_type_error_with_pos("getenv", STRING.type_of(), 0);
+    };
+
+    env::var(name.as_ref()).map_or_else(
+        |_| NULL.clone(),
+        |value| Reference::new(Object::String(value.into())),
+    )
+};
+
+/// Sets an environment variable for the current process and its children
+///
+/// Changing the environment is only sound while no other thread reads it. The interpreter runs on a
+/// single thread and the reads of `getenv` and `ALC_PATH` go through `std::env`, which takes the same lock
+/// as `set_var`. Embedders that run programs next to other threads, or next to C code that calls `getenv`,
+/// should leave this builtin out
+pub const SETENV: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 2 {
+        return new_args_len_error("setenv", &args, 2);
+    }
+
+    if let Some(err) = typecheck_args(
+        "setenv",
+        STRING.type_of(),
+        |obj| !matches!(obj.as_ref(), Object::String(_)),
+        &args,
+    ) {
+        return err;
+    }
+
+    let (Object::String(name), Object::String(value)) = (args[0].as_ref(), args[1].as_ref()) else {
+        panic!("This should never happen");
+    };
+
+    if name.is_empty() || name.contains(['=', '\0']) || value.contains('\0') {
+        return new_function_error("setenv", "invalid environment variable");
+    }
+
+    // not thread safe, see the documentation of the builtin
+    env::set_var(name.as_ref(), value.as_ref());
+
+    NULL.clone()
+};
+
+/// Exits the process with the exit code, from 0 to 255. defaults to 0
+pub const EXIT: BuiltinFunction = |_, args| {
+    if args.len() > 1 {
+        return new_args_len_error("exit", &args, 1);
+    }
+
+    let code = match args.first().map(|v| v.as_ref()) {
+        None => 0,
+        Some(Object::Integer(code @ 0..=255)) => *code as i32,
+        Some(Object::Integer(code)) => {
+            return new_function_error(
+                "exit",
+                format!("the exit code should be between 0 and 255 got {}", code),
+            )
+        }
+        Some(_) => return new_type_error_with_pos("exit", NUMBER.type_of(), 0),
+    };
+
+    let _ = std::io::stdout().flush();
+
+    process::exit(code)
+};
+
+/// Returns the id of the current process
+pub const PID: BuiltinFunction = |_, args| {
+    if !args.is_empty() {
+        return new_args_len_error("pid", &args, 0);
+    }
+
+    Reference::new(Object::Integer(process::id() as isize))
+};
 
 // TODO: Make this a format_args macro. then this function will just be a wrapper around format_args
 // TODO: with that we can have a : fprint (file print)
@@ -78,88 +190,823 @@ pub const PDEBUG: BuiltinFunction = |_, args| {
     NULL.clone()
 };
 
-/// Reads the current global file into a string
-pub const READ_FILE: BuiltinFunction = |_, _| {
-    let mut lock = OPEN_FILE.lock();
+/// Returns the current working directory
+pub const PWD: BuiltinFunction = |_, _| {
+    Reference::new(Object::String(
+        current_dir()
+            .expect("to get current dir")
+            .to_str()
+            .expect("to convert pathbuf to str")
+            .into(),
+    ))
+};
+
+/// Resource kind of the handles returned by `open`
+const FILE_KIND: &str = "file";
+
+/// File opened by `open`. Set to `None` when the file is closed
+type FileHandle = Option<BufReader<File>>;
+
+/// Runs `function` with the file handle passed as the first argument
+fn with_file<F>(name: &str, args: &[Reference], function: F) -> Reference
+where
+    F: FnOnce(&mut BufReader<File>) -> std::io::Result<Reference>,
+{
+    with_resource(name, args, FILE_KIND, |handle: &mut FileHandle| {
+        let Some(file) = handle.as_mut() else {
+            return Err(std::io::Error::other("file is closed"));
+        };
+
+        function(file)
+    })
+}
+
+/// Runs `function` with the value of the resource of type `kind` passed as the first argument
+fn with_resource<T, F>(name: &str, args: &[Reference], kind: &str, function: F) -> Reference
+where
+    T: 'static,
+    F: FnOnce(&mut T) -> std::io::Result<Reference>,
+{
+    let resource = match args.first().map(|v| v.as_ref()) {
+        Some(Object::Resource(resource)) if resource.kind == kind => resource,
+        Some(obj) => return new_type_error_with_got_and_pos(name, 0, kind, obj.type_of()),
+        None => return new_args_len_error(name, args, 1),
+    };
+
+    let mut lock = resource.value.lock();
+
+    let Some(value) = lock.downcast_mut::<T>() else {
+        return new_type_error_with_pos(name, kind, 0);
+    };
+
+    match function(value) {
+        Ok(value) => value,
+        Err(err) => new_function_error(name, err),
+    }
+}
+
+/// Returns the text that gets written into a file for a value
+fn output_string(value: &Reference) -> String {
+    match value.as_ref() {
+        Object::String(inner) => inner.to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// Opens a file and returns its handle.
+///
+/// Second argument is optional: the mode to open the file with `"r"` (read), `"w"` (write) or `"a"` (append). defaults to read
+pub const OPEN: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 1 && len != 2 {
+        return new_args_len_error("open", &args, 1);
+    }
+
+    let Object::String(path) = args[0].as_ref() else {
+        return new_type_error_with_pos("open", STRING.type_of(), 0);
+    };
+
+    let mode = match args.get(1).map(|v| v.as_ref()) {
+        None => "r",
+        Some(Object::String(mode)) => mode.as_ref(),
+        Some(_) => return new_type_error_with_pos("open", STRING.type_of(), 1),
+    };
+
+    let mut options = OpenOptions::new();
+
+    match mode {
+        "r" | "read" => options.read(true),
+        "w" | "write" => options.write(true).create(true).truncate(true),
+        "a" | "append" => options.append(true).create(true),
+        mode => {
+            return Reference::new(Object::Error(
+                format!(
+                    "Invalid file mode '{}': expected \"r\", \"w\" or \"a\"",
+                    mode
+                )
+                .into(),
+            ))
+        }
+    };
+
+    match options.open(path.as_ref()) {
+        Ok(file) => Reference::new(Object::Resource(Resource::new(
+            FILE_KIND,
+            Some(BufReader::new(file)) as FileHandle,
+        ))),
+        Err(err) => Reference::new(Object::Error(format!("Error opening file: {}", err).into())),
+    }
+};
+
+/// Closes a file handle. Closing an already closed file does nothing
+pub const CLOSE: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 1 {
+        return new_args_len_error("close", &args, 1);
+    }
+
+    let Object::Resource(resource) = args[0].as_ref() else {
+        return new_type_error_with_got_and_pos("close", 0, FILE_KIND, args[0].type_of());
+    };
+
+    let mut lock = resource.value.lock();
+
+    let Some(handle) = lock.downcast_mut::<FileHandle>() else {
+        return new_type_error_with_got_and_pos("close", 0, FILE_KIND, resource.kind);
+    };
+
+    *handle = None;
+
+    NULL.clone()
+};
+
+/// Reads the next line of a reader without the line ending. Returns null at the end of the input
+fn read_line_from(reader: &mut impl BufRead) -> std::io::Result<Reference> {
+    let mut line = String::new();
 
-    if lock.is_none() {
-        return Reference::new(Object::String("".into()));
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(NULL.clone());
     }
 
-    let mut file = lock.take().unwrap();
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+
+    Ok(Reference::new(Object::String(line.into())))
+}
+
+/// Reads the next line of a file or process, or of stdin if nothing is passed, without the line ending.
+/// Returns null at the end of the input
+pub const READ_LINE: BuiltinFunction = |_, args| match args.len() {
+    0 => read_line_from(&mut stdin().lock())
+        .unwrap_or_else(|err| new_function_error("read_line", err)),
+    1 => read_handle_line("read_line", &args),
+    _ => new_args_len_error("read_line", &args, 1),
+};
+
+/// Reads the next line of the file or process passed as the first argument
+fn read_handle_line(name: &str, args: &[Reference]) -> Reference {
+    match args[0].as_ref() {
+        Object::Resource(resource) if resource.kind == PROCESS_KIND => with_resource(
+            name,
+            args,
+            PROCESS_KIND,
+            |process: &mut ProcessHandle| match process.stdout.as_mut() {
+                Some(stdout) => read_line_from(stdout),
+                None => Ok(NULL.clone()),
+            },
+        ),
+        _ => with_file(name, args, read_line_from),
+    }
+}
+
+/// Splits a string into a list of lines.
+///
+/// For a file or process it returns a lazy sequence that reads a line each time the next item is needed
+pub const LINES: BuiltinFunction = |program, args| match args.first().map(|arg| arg.as_ref()) {
+    Some(Object::Resource(resource)) if args.len() == 1 => {
+        if resource.kind != FILE_KIND && resource.kind != PROCESS_KIND {
+            return new_type_error_with_got_and_pos("lines", 0, FILE_KIND, resource.kind);
+        }
+
+        let handle = args[0].clone();
+        let source: NativeSource = Box::new(std::iter::from_fn(move || {
+            let line = read_handle_line("lines", std::slice::from_ref(&handle));
+
+            (!matches!(line.as_ref(), Object::Null)).then_some(line)
+        }));
+
+        Reference::new(Object::Sequence(Sequence::new(Source::Native(source))))
+    }
+    _ => super::string::LINES(program, args),
+};
+
+/// Reads the rest of stdin into a string. Returns null if stdin is already at the end
+pub const READ_STDIN: BuiltinFunction = |_, args| {
+    if !args.is_empty() {
+        return new_args_len_error("read_stdin", &args, 0);
+    }
 
     let mut string = String::new();
 
-    file.read_to_string(&mut string)
-        .expect("error reading file");
+    match stdin().lock().read_to_string(&mut string) {
+        Ok(0) => NULL.clone(),
+        Ok(_) => Reference::new(Object::String(string.into())),
+        Err(err) => new_function_error("read_stdin", err),
+    }
+};
+
+/// Reads the rest of a file into a string
+pub const READ_ALL: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("read_all", &args, 1);
+    }
+
+    with_file("read_all", &args, |file| {
+        let mut string = String::new();
+        file.read_to_string(&mut string)?;
+
+        Ok(Reference::new(Object::String(string.into())))
+    })
+};
+
+/// Reads the whole file into a string, leaving the file at the start
+pub const READ_FILE: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("read_file", &args, 1);
+    }
 
-    file.seek(std::io::SeekFrom::Start(0))
-        .expect("trying to seek file");
+    with_file("read_file", &args, |file| {
+        let mut string = String::new();
 
-    let _ = lock.insert(file);
+        file.rewind()?;
+        file.read_to_string(&mut string)?;
+        file.rewind()?;
 
-    Reference::new(Object::String(string.into()))
+        Ok(Reference::new(Object::String(string.into())))
+    })
 };
 
-/// Returns the current working directory
-pub const PWD: BuiltinFunction = |_, _| {
-    Reference::new(Object::String(
-        current_dir()
-            .expect("to get current dir")
-            .to_str()
-            .expect("to convert pathbuf to str")
-            .into(),
-    ))
+/// Writes the arguments into a file. strings are written without quotes
+pub const WRITE: BuiltinFunction = |_, args| {
+    if args.len() < 2 {
+        return new_args_len_error("write", &args, 2);
+    }
+
+    with_file("write", &args, |file| {
+        // Moves the file to the position of the reader, dropping the buffered data
+        let position = file.stream_position()?;
+        file.seek(SeekFrom::Start(position))?;
+
+        let file = file.get_mut();
+
+        for value in args.iter().skip(1) {
+            file.write_all(output_string(value).as_bytes())?;
+        }
+
+        Ok(NULL.clone())
+    })
+};
+
+/// Flushes the pending writes of a file
+pub const FLUSH: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("flush", &args, 1);
+    }
+
+    with_file("flush", &args, |file| {
+        file.get_mut().flush()?;
+
+        Ok(NULL.clone())
+    })
+};
+
+/// Moves the position of a file and returns the new position.
+///
+/// Positive offsets are from the start of the file and negative offsets from the end
+pub const SEEK: BuiltinFunction = |_, args| {
+    if args.len() != 2 {
+        return new_args_len_error("seek", &args, 2);
+    }
+
+    let Object::Integer(offset) = args[1].as_ref() else {
+        return new_type_error_with_pos("seek", NUMBER.type_of(), 1);
+    };
+
+    let position = if *offset >= 0 {
+        SeekFrom::Start(*offset as u64)
+    } else {
+        SeekFrom::End(*offset as i64)
+    };
+
+    with_file("seek", &args, |file| {
+        let position = file.seek(position)?;
+
+        Ok(Reference::new(Object::Integer(position as isize)))
+    })
+};
+
+/// Reads a whole file into a string from its path
+pub const SLURP: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("slurp", &args, 1);
+    }
+
+    let Object::String(path) = args[0].as_ref() else {
+        return new_type_error_with_pos("slurp", STRING.type_of(), 0);
+    };
+
+    match std::fs::read_to_string(path.as_ref()) {
+        Ok(content) => Reference::new(Object::String(content.into())),
+        Err(err) => new_function_error("slurp", err),
+    }
+};
+
+/// Writes a value into a file from its path, replacing its content.
+///
+/// Third argument is optional: if truthy the value is appended to the file instead
+pub const SPIT: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 2 && len != 3 {
+        return new_args_len_error("spit", &args, 2);
+    }
+
+    let Object::String(path) = args[0].as_ref() else {
+        return new_type_error_with_pos("spit", STRING.type_of(), 0);
+    };
+
+    let append = args.get(2).is_some_and(is_truthy);
+
+    let result = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(path.as_ref())
+        .and_then(|mut file| file.write_all(output_string(&args[1]).as_bytes()));
+
+    match result {
+        Ok(_) => NULL.clone(),
+        Err(err) => new_function_error("spit", err),
+    }
+};
+
+/// Returns the path passed as the first argument of a function with a single path argument
+fn path_arg<'a>(name: &str, args: &'a [Reference]) -> Result<&'a Path, Reference> {
+    if args.len() != 1 {
+        return Err(new_args_len_error(name, args, 1));
+    }
+
+    let Object::String(path) = args[0].as_ref() else {
+        return Err(new_type_error_with_pos(name, STRING.type_of(), 0));
+    };
+
+    Ok(Path::new(path.as_ref()))
+}
+
+/// Returns the source and destination paths of functions like `rename` and `copy`
+fn path_pair_args<'a>(
+    name: &str,
+    args: &'a [Reference],
+) -> Result<(&'a Path, &'a Path), Reference> {
+    if args.len() != 2 {
+        return Err(new_args_len_error(name, args, 2));
+    }
+
+    let Object::String(from) = args[0].as_ref() else {
+        return Err(new_type_error_with_pos(name, STRING.type_of(), 0));
+    };
+    let Object::String(to) = args[1].as_ref() else {
+        return Err(new_type_error_with_pos(name, STRING.type_of(), 1));
+    };
+
+    Ok((Path::new(from.as_ref()), Path::new(to.as_ref())))
+}
+
+fn path_to_object(path: &Path) -> Reference {
+    Reference::new(Object::String(path.to_string_lossy().into()))
+}
+
+/// Returns the names of the entries of a directory sorted alphabetically
+pub const LIST_DIR: BuiltinFunction = |_, args| {
+    let path = match path_arg("list_dir", &args) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+
+    let entries = fs::read_dir(path).and_then(|entries| {
+        entries
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect::<std::io::Result<Vec<_>>>()
+    });
+
+    match entries {
+        Ok(mut entries) => {
+            entries.sort();
+
+            Reference::new(Object::List(
+                entries
+                    .into_iter()
+                    .map(|entry| Reference::new(Object::String(entry.into())))
+                    .collect(),
+            ))
+        }
+        Err(err) => new_function_error("list_dir", err),
+    }
+};
+
+/// Returns true if the path exists
+pub const EXISTS: BuiltinFunction = |_, args| match path_arg("exists?", &args) {
+    Ok(path) => bool_from_native(path.exists()),
+    Err(err) => err,
+};
+
+/// Returns true if the path exists and is a directory
+pub const IS_DIR: BuiltinFunction = |_, args| match path_arg("is_dir?", &args) {
+    Ok(path) => bool_from_native(path.is_dir()),
+    Err(err) => err,
+};
+
+/// Creates a directory and all of its missing parents
+pub const MKDIR: BuiltinFunction = |_, args| {
+    let path = match path_arg("mkdir", &args) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+
+    match fs::create_dir_all(path) {
+        Ok(_) => NULL.clone(),
+        Err(err) => new_function_error("mkdir", err),
+    }
 };
 
-/// Returns the current global file descriptor as a string
-pub const FILE: BuiltinFunction = |_, _| {
-    let lock = OPEN_FILE.lock();
+/// Removes a file or an empty directory.
+///
+/// Second argument is optional: if truthy directories are removed with all of their contents
+pub const REMOVE: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 1 && len != 2 {
+        return new_args_len_error("remove", &args, 1);
+    }
+
+    let path = match path_arg("remove", &args[..1]) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+
+    let recursive = args.get(1).is_some_and(is_truthy);
 
-    if let Some(file) = lock.as_ref() {
-        Reference::new(Object::String(format!("FILE[{:p}]", file).into()))
+    let result = if !path.is_dir() {
+        fs::remove_file(path)
+    } else if recursive {
+        fs::remove_dir_all(path)
     } else {
-        NULL.clone()
+        fs::remove_dir(path)
+    };
+
+    match result {
+        Ok(_) => NULL.clone(),
+        Err(err) => new_function_error("remove", err),
     }
 };
 
-/// Opens a file into the global file descriptor
-pub const OPEN: BuiltinFunction = |_, args| {
+/// Renames or moves a file or directory
+pub const RENAME: BuiltinFunction = |_, args| {
+    let (from, to) = match path_pair_args("rename", &args) {
+        Ok(paths) => paths,
+        Err(err) => return err,
+    };
+
+    match fs::rename(from, to) {
+        Ok(_) => NULL.clone(),
+        Err(err) => new_function_error("rename", err),
+    }
+};
+
+/// Copies a file and returns the amount of bytes copied
+pub const COPY: BuiltinFunction = |_, args| {
+    let (from, to) = match path_pair_args("copy", &args) {
+        Ok(paths) => paths,
+        Err(err) => return err,
+    };
+
+    match fs::copy(from, to) {
+        Ok(bytes) => Reference::new(Object::Integer(bytes as isize)),
+        Err(err) => new_function_error("copy", err),
+    }
+};
+
+/// Returns the size of a file in bytes
+pub const FILE_SIZE: BuiltinFunction = |_, args| {
+    let path = match path_arg("file_size", &args) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+
+    match fs::metadata(path) {
+        Ok(metadata) => Reference::new(Object::Integer(metadata.len() as isize)),
+        Err(err) => new_function_error("file_size", err),
+    }
+};
+
+/// Returns the paths matching a glob pattern sorted alphabetically
+pub const GLOB: BuiltinFunction = |_, args| {
     let len = args.len();
     if len != 1 {
-        return new_args_len_error("open", &args, 1);
+        return new_args_len_error("glob", &args, 1);
     }
 
-    if let Some(err) = typecheck_args(
-        "open",
-        STRING.type_of(),
-        |obj| !matches!(obj.as_ref(), Object::String(_)),
-        &args,
-    ) {
-        return err;
+    let Object::String(pattern) = args[0].as_ref() else {
+        return new_type_error_with_pos("glob", STRING.type_of(), 0);
+    };
+
+    let paths = match glob::glob(pattern) {
+        Ok(paths) => paths,
+        Err(err) => return new_function_error("glob", err),
+    };
+
+    match paths.collect::<Result<Vec<_>, _>>() {
+        Ok(paths) => Reference::new(Object::List(
+            paths.iter().map(|path| path_to_object(path)).collect(),
+        )),
+        Err(err) => new_function_error("glob", err),
     }
+};
 
-    let Object::String(inner) = args[0].as_ref() else {
-        panic!("This should never happen");
+/// Joins the arguments into a single path
+pub const PATH_JOIN: BuiltinFunction = |_, args| {
+    if args.is_empty() {
+        return new_args_len_error("path_join", &args, 1);
+    }
+
+    let mut path = PathBuf::new();
+
+    for (idx, arg) in args.iter().enumerate() {
+        let Object::String(part) = arg.as_ref() else {
+            return new_type_error_with_pos("path_join", STRING.type_of(), idx);
+        };
+
+        path.push(part.as_ref());
+    }
+
+    path_to_object(&path)
+};
+
+/// Returns the last component of a path. null if the path has none
+pub const BASENAME: BuiltinFunction = |_, args| match path_arg("basename", &args) {
+    Ok(path) => path.file_name().map_or_else(
+        || NULL.clone(),
+        |name| Reference::new(Object::String(name.to_string_lossy().into())),
+    ),
+    Err(err) => err,
+};
+
+/// Returns the path without its last component. null if the path has no parent
+pub const DIRNAME: BuiltinFunction = |_, args| match path_arg("dirname", &args) {
+    Ok(path) => path.parent().map_or_else(|| NULL.clone(), path_to_object),
+    Err(err) => err,
+};
+
+/// Returns the extension of a path without the leading dot. null if the path has no extension
+pub const EXTENSION: BuiltinFunction = |_, args| match path_arg("extension", &args) {
+    Ok(path) => path.extension().map_or_else(
+        || NULL.clone(),
+        |extension| Reference::new(Object::String(extension.to_string_lossy().into())),
+    ),
+    Err(err) => err,
+};
+
+/// Returns the absolute version of a path, the path doesn't need to exist
+pub const ABSOLUTE: BuiltinFunction = |_, args| {
+    let path = match path_arg("absolute", &args) {
+        Ok(path) => path,
+        Err(err) => return err,
     };
 
-    let file = File::open(inner.as_ref());
+    match path::absolute(path) {
+        Ok(path) => path_to_object(&path),
+        Err(err) => new_function_error("absolute", err),
+    }
+};
+
+/// Resource kind of the handles returned by `spawn`
+const PROCESS_KIND: &str = "process";
 
-    match file {
-        Ok(file) => {
-            *OPEN_FILE.lock() = Some(file);
+/// Process started by `spawn`
+struct ProcessHandle {
+    child: Child,
+    /// Set to `None` once the process is waited on
+    stdout: Option<BufReader<ChildStdout>>,
+}
+
+/// Builds the command for `exec` and `spawn` from their arguments: `program [args] {"cwd" "env"}`
+fn command_from_args(
+    name: &str,
+    program: &Program,
+    args: &[Reference],
+) -> Result<Command, Reference> {
+    if program.sandboxed() {
+        return Err(new_function_error(
+            name,
+            "running processes is disabled in sandboxed programs",
+        ));
+    }
+
+    let len = args.len();
+    if !(1..=3).contains(&len) {
+        return Err(new_args_len_error(name, args, 2));
+    }
+
+    let Object::String(path) = args[0].as_ref() else {
+        return Err(new_type_error_with_pos(name, STRING.type_of(), 0));
+    };
+
+    let mut command = Command::new(path.as_ref());
+
+    match args.get(1).map(|v| v.as_ref()) {
+        Some(Object::List(list)) => {
+            for arg in list.iter() {
+                let Object::String(arg) = arg.as_ref() else {
+                    return Err(new_type_error_with_pos(name, STRING.type_of(), 1));
+                };
+
+                command.arg(arg.as_ref());
+            }
         }
-        Err(err) => {
-            return Reference::new(Object::Error(format!("Error opening file: {}", err).into()));
+        Some(_) => return Err(new_type_error_with_pos(name, LIST.type_of(), 1)),
+        None => {}
+    }
+
+    match args.get(2).map(|v| v.as_ref()) {
+        Some(Object::Map(options)) => set_command_options(name, &mut command, options)?,
+        Some(_) => return Err(new_type_error_with_pos(name, MAP.type_of(), 2)),
+        None => {}
+    }
+
+    Ok(command)
+}
+
+/// Applies the `cwd` and `env` options to a command
+fn set_command_options(name: &str, command: &mut Command, options: &Map) -> Result<(), Reference> {
+    match options.get("cwd").map(|v| v.as_ref()) {
+        Some(Object::String(cwd)) => {
+            command.current_dir(cwd.as_ref());
+        }
+        Some(_) => return Err(new_function_error(name, "option 'cwd' should be a string")),
+        None => {}
+    }
+
+    match options.get("env").map(|v| v.as_ref()) {
+        Some(Object::Map(vars)) => {
+            for (key, value) in vars.iter() {
+                let Object::String(value) = value.as_ref() else {
+                    return Err(new_function_error(
+                        name,
+                        "option 'env' should only contain strings",
+                    ));
+                };
+
+                command.env(key.as_ref(), value.as_ref());
+            }
         }
+        Some(_) => return Err(new_function_error(name, "option 'env' should be a map")),
+        None => {}
+    }
+
+    Ok(())
+}
+
+fn exit_code_object(status: process::ExitStatus) -> Reference {
+    status.code().map_or_else(
+        || NULL.clone(),
+        |code| Reference::new(Object::Integer(code as isize)),
+    )
+}
+
+/// Runs a command until it finishes and returns a map with its exit `code`, `stdout` and `stderr`.
+///
+/// Second argument is optional: list of arguments passed into the command
+///
+/// Third argument is optional: map of options. `cwd` the working directory and `env` a map of environment variables
+pub const EXEC: BuiltinFunction = |program, args| {
+    let mut command = match command_from_args("exec", program, &args) {
+        Ok(command) => command,
+        Err(err) => return err,
     };
 
-    NULL.clone()
+    let output = match command.stdin(Stdio::null()).output() {
+        Ok(output) => output,
+        Err(err) => return new_function_error("exec", err),
+    };
+
+    let mut result = Map::new();
+
+    result.insert("code".into(), exit_code_object(output.status));
+    result.insert(
+        "stdout".into(),
+        Reference::new(Object::String(
+            String::from_utf8_lossy(&output.stdout).into(),
+        )),
+    );
+    result.insert(
+        "stderr".into(),
+        Reference::new(Object::String(
+            String::from_utf8_lossy(&output.stderr).into(),
+        )),
+    );
+
+    Reference::new(Object::Map(Arc::new(result)))
 };
 
-///Closes the global file descriptor
-pub const CLOSE: BuiltinFunction = |_, _| {
-    *OPEN_FILE.lock() = None;
+/// Starts a command and returns its handle. Its output can be read with `read_line`.
+///
+/// Takes the same arguments as `exec`
+pub const SPAWN: BuiltinFunction = |program, args| {
+    let mut command = match command_from_args("spawn", program, &args) {
+        Ok(command) => command,
+        Err(err) => return err,
+    };
 
-    NULL.clone()
+    let mut child = match command.stdout(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(err) => return new_function_error("spawn", err),
+    };
+
+    let stdout = child.stdout.take().map(BufReader::new);
+
+    Reference::new(Object::Resource(Resource::new(
+        PROCESS_KIND,
+        ProcessHandle { child, stdout },
+    )))
 };
+
+/// Waits for a process to finish and returns its exit code. null if the process was killed by a signal
+pub const WAIT: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("wait", &args, 1);
+    }
+
+    with_resource(
+        "wait",
+        &args,
+        PROCESS_KIND,
+        |process: &mut ProcessHandle| {
+            process.stdout = None;
+
+            Ok(exit_code_object(process.child.wait()?))
+        },
+    )
+};
+
+/// Kills a process
+pub const KILL: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("kill", &args, 1);
+    }
+
+    with_resource(
+        "kill",
+        &args,
+        PROCESS_KIND,
+        |process: &mut ProcessHandle| {
+            process.child.kill()?;
+
+            Ok(NULL.clone())
+        },
+    )
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::{objects::Object, Reference},
+        test::new_test_program,
+    };
+
+    use super::{EXEC, EXIT, SPAWN};
+
+    #[test]
+    fn exit_rejects_invalid_codes_without_exiting() {
+        let mut program = new_test_program();
+
+        let invalid = [
+            vec![Reference::new(Object::Integer(256))],
+            vec![Reference::new(Object::Integer(-1))],
+            vec![Reference::new(Object::Integer(isize::MAX))],
+            vec![
+                Reference::new(Object::Integer(0)),
+                Reference::new(Object::Integer(1)),
+            ],
+        ];
+
+        for args in invalid {
+            let result = EXIT(&mut program, args.clone());
+
+            assert!(
+                matches!(result.as_ref(), Object::Error(_)),
+                "exit with {:?} should return an error",
+                args
+            );
+        }
+    }
+
+    #[test]
+    fn sandbox_blocks_running_processes() {
+        let mut program = new_test_program();
+        program.set_sandboxed(true);
+
+        for function in [EXEC, SPAWN] {
+            let args = vec![Reference::new(Object::String("echo".into()))];
+            let result = function(&mut program, args);
+
+            let Object::Error(err) = result.as_ref() else {
+                panic!("expected an error got {}", result);
+            };
+
+            assert!(err.contains("disabled in sandboxed programs"), "{}", err);
+        }
+    }
+}
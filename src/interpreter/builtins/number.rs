@@ -1,11 +1,24 @@
 //! Builtin functions for arithmetic operations
+use std::cmp::Ordering;
+
 use crate::interpreter::{
+    bool_from_native,
     builtins::type_check,
     objects::{BuiltinFunction, Object},
     Env, Reference, NUMBER,
 };
 
-use super::{errors::new_args_len_error, typecheck_args, unwrap_args};
+use super::{
+    add_std_builtins,
+    errors::{new_args_len_error, new_function_error, new_type_error_with_got_and_pos},
+    unwrap_args,
+};
+
+/// Type name used in the errors of functions that accept both integers and floats
+const NUMERIC: &str = "number or float";
+
+const OVERFLOW: &str = "integer overflow";
+const DIVISION_BY_ZERO: &str = "division by zero";
 
 /// Add arithmetic builtins to the environment
 pub fn add_number_builtins(env: &mut Env) {
@@ -16,122 +29,284 @@ pub fn add_number_builtins(env: &mut Env) {
         ("/", DIVIDE),
         ("%", MOD),
         ("parse_int", PARSE_INT),
+        ("parse_float", PARSE_FLOAT),
         ("abs", ABS),
+        ("min", MIN),
+        ("max", MAX),
+        ("pow", POW),
+        ("sqrt", SQRT),
+        ("gcd", GCD),
+        ("lcm", LCM),
+        ("clamp", CLAMP),
+        ("sign", SIGN),
+        ("even?", EVEN),
+        ("odd?", ODD),
+        ("div_floor", DIV_FLOOR),
+        ("mod_floor", MOD_FLOOR),
+        ("float", FLOAT),
+        ("int", INT),
+        ("floor", FLOOR),
+        ("ceil", CEIL),
+        ("round", ROUND),
+        ("sin", SIN),
+        ("cos", COS),
+        ("tan", TAN),
+        ("asin", ASIN),
+        ("acos", ACOS),
+        ("atan", ATAN),
+        ("atan2", ATAN2),
+        ("exp", EXP),
+        ("ln", LN),
+        ("log2", LOG2),
+        ("log10", LOG10),
     ];
 
-    functions
-        .into_iter()
-        .map(|(name, function)| (name, Reference::new(Object::Builtin { function })))
-        .for_each(|(name, function)| {
-            env.insert(name.into(), function.clone());
-            env.insert(("std/".to_owned() + name).into(), function);
-        });
+    add_std_builtins(env, functions);
 }
 
-/// Adds numbers
-pub const ADD: BuiltinFunction = |_, args| {
-    if let Some(err) = typecheck_args(
-        "+",
-        NUMBER.type_of(),
-        |obj| !matches!(obj.as_ref(), Object::Integer(_)),
-        &args,
-    ) {
-        return err;
-    };
+/// Numeric value of an argument. Integers are promoted into floats when mixed with them
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Number {
+    Integer(isize),
+    Float(f64),
+}
 
-    let mut sum = 0;
+impl Number {
+    pub(crate) fn from_object(object: &Object) -> Option<Number> {
+        match object {
+            Object::Integer(value) => Some(Number::Integer(*value)),
+            Object::Float(value) => Some(Number::Float(*value)),
+            _ => None,
+        }
+    }
 
-    for obj in args.iter() {
-        let Object::Integer(n) = obj.as_ref() else {
-            panic!("This should never happen")
-        };
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Integer(value) => value as f64,
+            Number::Float(value) => value,
+        }
+    }
 
-        sum += n;
+    fn into_object(self) -> Reference {
+        match self {
+            Number::Integer(value) => Reference::new(Object::Integer(value)),
+            Number::Float(value) => Reference::new(Object::Float(value)),
+        }
     }
+}
 
-    Reference::new(Object::Integer(sum))
-};
+/// Compares two numeric objects. None if one of them is not a number or is NaN
+pub(crate) fn compare_numbers(left: &Object, right: &Object) -> Option<Ordering> {
+    match (Number::from_object(left)?, Number::from_object(right)?) {
+        (Number::Integer(l), Number::Integer(r)) => Some(l.cmp(&r)),
+        (Number::Float(l), Number::Float(r)) => l.partial_cmp(&r),
+        (Number::Integer(l), Number::Float(r)) => compare_integer_float(l, r),
+        (Number::Float(l), Number::Integer(r)) => {
+            compare_integer_float(r, l).map(Ordering::reverse)
+        }
+    }
+}
 
-/// Subtracts numbers
-pub const SUBTRACT: BuiltinFunction = |_, args| {
-    if let Some(err) = typecheck_args(
-        "-",
-        NUMBER.type_of(),
-        |obj| !matches!(obj.as_ref(), Object::Integer(_)),
-        &args,
-    ) {
-        return err;
+/// Compares an integer with a float without rounding the integer. None if the float is NaN
+pub(crate) fn compare_integer_float(integer: isize, float: f64) -> Option<Ordering> {
+    if float.is_nan() {
+        return None;
+    }
+
+    // the cast saturates, so floats out of range are still above or below every integer
+    let whole = float.trunc();
+    let ordering = (integer as i128).cmp(&(whole as i128));
+    // with the same whole part the fraction decides
+    Some(ordering.then_with(|| whole.partial_cmp(&float).unwrap_or(Ordering::Equal)))
+}
+
+/// Returns the arguments as numbers or a type error
+fn number_args(name: &str, args: &[Reference]) -> Result<Vec<Number>, Reference> {
+    args.iter()
+        .enumerate()
+        .map(|(pos, arg)| {
+            Number::from_object(arg)
+                .ok_or_else(|| new_type_error_with_got_and_pos(name, pos, NUMERIC, arg.type_of()))
+        })
+        .collect()
+}
+
+/// Folds the arguments with `integer` while they are all integers and with `float` once a float is found.
+///
+/// `empty` is returned when there are no arguments, if it's `None` at least one argument is required
+fn fold_numbers(
+    name: &str,
+    args: &[Reference],
+    empty: Option<isize>,
+    integer: fn(isize, isize) -> Result<isize, &'static str>,
+    float: fn(f64, f64) -> Result<f64, &'static str>,
+) -> Reference {
+    let numbers = match number_args(name, args) {
+        Ok(numbers) => numbers,
+        Err(err) => return err,
     };
 
-    let Object::Integer(mut total) = args[0].as_ref() else {
-        panic!("This should never happen");
+    let Some(first) = numbers.first().copied().or(empty.map(Number::Integer)) else {
+        return new_args_len_error(name, args, 1);
     };
 
-    for obj in args.iter().skip(1) {
-        let Object::Integer(n) = obj.as_ref() else {
-            panic!("This should never happen")
-        };
+    let result = numbers
+        .iter()
+        .skip(1)
+        .try_fold(first, |total, number| match (total, number) {
+            (Number::Integer(l), Number::Integer(r)) => integer(l, *r).map(Number::Integer),
+            (l, r) => float(l.as_f64(), r.as_f64()).map(Number::Float),
+        });
 
-        total -= n;
+    match result {
+        Ok(result) => result.into_object(),
+        Err(err) => new_function_error(name, err),
     }
+}
 
-    Reference::new(Object::Integer(total))
-};
-
-/// Multiplies numbers
-pub const MULTIPLY: BuiltinFunction = |_, args| {
-    if let Some(err) = typecheck_args(
-        "*",
-        NUMBER.type_of(),
-        |obj| !matches!(obj.as_ref(), Object::Integer(_)),
-        &args,
-    ) {
-        return err;
+/// Applies a float function to a single numeric argument
+fn unary_float(name: &str, args: &[Reference], function: fn(f64) -> f64) -> Reference {
+    if args.len() != 1 {
+        return new_args_len_error(name, args, 1);
     }
 
-    let Object::Integer(mut total) = args[0].as_ref() else {
-        panic!("This should never happen");
+    let Some(number) = Number::from_object(&args[0]) else {
+        return new_type_error_with_got_and_pos(name, 0, NUMERIC, args[0].type_of());
     };
 
-    for obj in args.iter().skip(1) {
-        let Object::Integer(n) = obj.as_ref() else {
-            panic!("This should never happen")
-        };
+    let input = number.as_f64();
+    let result = function(input);
 
-        total *= n;
+    if result.is_nan() && !input.is_nan() {
+        return new_function_error(name, format!("{:?} is outside of the domain", input));
     }
 
-    Reference::new(Object::Integer(total))
-};
+    Reference::new(Object::Float(result))
+}
 
-/// Divides numbers
-pub const DIVIDE: BuiltinFunction = |_, args| {
-    if let Some(err) = typecheck_args(
-        "/",
-        NUMBER.type_of(),
-        |obj| !matches!(obj.as_ref(), Object::Integer(_)),
-        &args,
-    ) {
-        return err;
+/// Converts a float into an integer using `function` to round it
+fn float_to_integer(name: &str, args: &[Reference], function: fn(f64) -> f64) -> Reference {
+    if args.len() != 1 {
+        return new_args_len_error(name, args, 1);
     }
 
-    let Object::Integer(mut total) = args[0].as_ref() else {
-        panic!("This should never happen");
-    };
+    match Number::from_object(&args[0]) {
+        Some(Number::Integer(_)) => args[0].clone(),
+        Some(Number::Float(value)) => {
+            let value = function(value);
 
-    for obj in args.iter().skip(1) {
-        let Object::Integer(n) = obj.as_ref() else {
-            panic!("This should never happen")
-        };
+            if !value.is_finite() || value < isize::MIN as f64 || value >= isize::MAX as f64 {
+                return new_function_error(name, format!("{:?} does not fit in a number", value));
+            }
 
-        if n == &0 {
-            return Reference::new(Object::Error("division by zero".into()));
+            Reference::new(Object::Integer(value as isize))
         }
+        None => new_type_error_with_got_and_pos(name, 0, NUMERIC, args[0].type_of()),
+    }
+}
 
-        total /= n;
+/// Returns the two integer arguments of a function or an error
+fn integer_pair(name: &str, args: &[Reference]) -> Result<(isize, isize), Reference> {
+    if args.len() != 2 {
+        return Err(new_args_len_error(name, args, 2));
     }
 
-    Reference::new(Object::Integer(total))
+    match (args[0].as_ref(), args[1].as_ref()) {
+        (Object::Integer(l), Object::Integer(r)) => Ok((*l, *r)),
+        (Object::Integer(_), obj) => Err(new_type_error_with_got_and_pos(
+            name,
+            1,
+            NUMBER.type_of(),
+            obj.type_of(),
+        )),
+        (obj, _) => Err(new_type_error_with_got_and_pos(
+            name,
+            0,
+            NUMBER.type_of(),
+            obj.type_of(),
+        )),
+    }
+}
+
+/// Floored division, rounding towards negative infinity
+fn div_floor(l: isize, r: isize) -> Result<isize, &'static str> {
+    if r == 0 {
+        return Err(DIVISION_BY_ZERO);
+    }
+
+    let quotient = l.checked_div(r).ok_or(OVERFLOW)?;
+
+    if l % r != 0 && ((l < 0) != (r < 0)) {
+        Ok(quotient - 1)
+    } else {
+        Ok(quotient)
+    }
+}
+
+fn gcd(l: isize, r: isize) -> Result<isize, &'static str> {
+    let (mut a, mut b) = (l.unsigned_abs(), r.unsigned_abs());
+
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    isize::try_from(a).map_err(|_| OVERFLOW)
+}
+
+/// Adds numbers
+pub const ADD: BuiltinFunction = |_, args| {
+    fold_numbers(
+        "+",
+        &args,
+        Some(0),
+        |l, r| l.checked_add(r).ok_or(OVERFLOW),
+        |l, r| Ok(l + r),
+    )
+};
+
+/// Subtracts numbers
+pub const SUBTRACT: BuiltinFunction = |_, args| {
+    fold_numbers(
+        "-",
+        &args,
+        None,
+        |l, r| l.checked_sub(r).ok_or(OVERFLOW),
+        |l, r| Ok(l - r),
+    )
+};
+
+/// Multiplies numbers
+pub const MULTIPLY: BuiltinFunction = |_, args| {
+    fold_numbers(
+        "*",
+        &args,
+        Some(1),
+        |l, r| l.checked_mul(r).ok_or(OVERFLOW),
+        |l, r| Ok(l * r),
+    )
+};
+
+/// Divides numbers. Integer division truncates the result
+pub const DIVIDE: BuiltinFunction = |_, args| {
+    fold_numbers(
+        "/",
+        &args,
+        None,
+        |l, r| {
+            if r == 0 {
+                return Err(DIVISION_BY_ZERO);
+            }
+
+            l.checked_div(r).ok_or(OVERFLOW)
+        },
+        |l, r| {
+            if r == 0.0 {
+                return Err(DIVISION_BY_ZERO);
+            }
+
+            Ok(l / r)
+        },
+    )
 };
 
 pub const PARSE_INT: BuiltinFunction = |_, args| {
@@ -150,16 +325,401 @@ pub const PARSE_INT: BuiltinFunction = |_, args| {
     }
 };
 
+pub const PARSE_FLOAT: BuiltinFunction = |_, args| {
+    type_check!("parse_float", args, [Object::String(_)]);
+    unwrap_args!(args, [Object::String(input)]);
+
+    if let Ok(value) = input.trim().parse::<f64>() {
+        Reference::new(Object::Float(value))
+    } else {
+        Reference::new(Object::Error("Could not parse float".into()))
+    }
+};
+
+/// Remainder of the integer division, it has the same sign as the dividend
 pub const MOD: BuiltinFunction = |_, args| {
-    type_check!("mod", args, [Object::Integer(_), Object::Integer(_)]);
+    type_check!("%", args, [Object::Integer(_), Object::Integer(_)]);
     unwrap_args!(args, [Object::Integer(l), Object::Integer(r)]);
 
-    Reference::new(Object::Integer(l % r))
+    if *r == 0 {
+        return new_function_error("%", DIVISION_BY_ZERO);
+    }
+
+    // checked_rem only fails for isize::MIN % -1 where the remainder is 0
+    Reference::new(Object::Integer(l.checked_rem(*r).unwrap_or(0)))
 };
 
 pub const ABS: BuiltinFunction = |_, args| {
-    type_check!("mod", args, [Object::Integer(_)]);
-    unwrap_args!(args, [Object::Integer(l)]);
+    type_check!("abs", args, [Object::Integer(_) | Object::Float(_)]);
 
-    Reference::new(Object::Integer(l.abs()))
+    match args[0].as_ref() {
+        Object::Integer(l) => l.checked_abs().map_or_else(
+            || new_function_error("abs", OVERFLOW),
+            |value| Reference::new(Object::Integer(value)),
+        ),
+        Object::Float(l) => Reference::new(Object::Float(l.abs())),
+        _ => unreachable!(),
+    }
 };
+
+/// Returns the smallest argument
+pub const MIN: BuiltinFunction = |_, args| {
+    if let Err(err) = number_args("min", &args) {
+        return err;
+    }
+
+    let Some(first) = args.first() else {
+        return new_args_len_error("min", &args, 1);
+    };
+
+    args.iter()
+        .skip(1)
+        .fold(first, |min, arg| {
+            if compare_numbers(arg, min) == Some(Ordering::Less) {
+                arg
+            } else {
+                min
+            }
+        })
+        .clone()
+};
+
+/// Returns the largest argument
+pub const MAX: BuiltinFunction = |_, args| {
+    if let Err(err) = number_args("max", &args) {
+        return err;
+    }
+
+    let Some(first) = args.first() else {
+        return new_args_len_error("max", &args, 1);
+    };
+
+    args.iter()
+        .skip(1)
+        .fold(first, |max, arg| {
+            if compare_numbers(arg, max) == Some(Ordering::Greater) {
+                arg
+            } else {
+                max
+            }
+        })
+        .clone()
+};
+
+/// Raises the first argument to the power of the second.
+///
+/// Integers raised to non negative integers return an integer
+pub const POW: BuiltinFunction = |_, args| {
+    if args.len() != 2 {
+        return new_args_len_error("pow", &args, 2);
+    }
+
+    let numbers = match number_args("pow", &args) {
+        Ok(numbers) => numbers,
+        Err(err) => return err,
+    };
+
+    match (numbers[0], numbers[1]) {
+        (Number::Integer(base), Number::Integer(exp)) if exp >= 0 => u32::try_from(exp)
+            .ok()
+            .and_then(|exp| base.checked_pow(exp))
+            .map_or_else(
+                || new_function_error("pow", OVERFLOW),
+                |value| Reference::new(Object::Integer(value)),
+            ),
+        (base, exp) => Reference::new(Object::Float(base.as_f64().powf(exp.as_f64()))),
+    }
+};
+
+pub const SQRT: BuiltinFunction = |_, args| unary_float("sqrt", &args, f64::sqrt);
+
+/// Greatest common divisor of the arguments
+pub const GCD: BuiltinFunction = |_, args| {
+    type_check!("gcd", args, Object::Integer(_));
+
+    fold_numbers("gcd", &args, Some(0), gcd, |_, _| unreachable!())
+};
+
+/// Least common multiple of the arguments
+pub const LCM: BuiltinFunction = |_, args| {
+    type_check!("lcm", args, Object::Integer(_));
+
+    fold_numbers(
+        "lcm",
+        &args,
+        Some(1),
+        |l, r| {
+            if l == 0 || r == 0 {
+                return Ok(0);
+            }
+
+            (l / gcd(l, r)?)
+                .checked_mul(r)
+                .and_then(isize::checked_abs)
+                .ok_or(OVERFLOW)
+        },
+        |_, _| unreachable!(),
+    )
+};
+
+/// Restricts the first argument between the second and the third
+pub const CLAMP: BuiltinFunction = |_, args| {
+    if args.len() != 3 {
+        return new_args_len_error("clamp", &args, 3);
+    }
+
+    if let Err(err) = number_args("clamp", &args) {
+        return err;
+    }
+
+    let (value, low, high) = (&args[0], &args[1], &args[2]);
+
+    if compare_numbers(low, high) == Some(Ordering::Greater) {
+        return new_function_error("clamp", "the lower bound is greater than the upper bound");
+    }
+
+    if compare_numbers(value, low) == Some(Ordering::Less) {
+        low.clone()
+    } else if compare_numbers(value, high) == Some(Ordering::Greater) {
+        high.clone()
+    } else {
+        value.clone()
+    }
+};
+
+/// Returns -1, 0 or 1 depending on the sign of the argument
+pub const SIGN: BuiltinFunction = |_, args| {
+    type_check!("sign", args, [Object::Integer(_) | Object::Float(_)]);
+
+    match args[0].as_ref() {
+        Object::Integer(value) => Reference::new(Object::Integer(value.signum())),
+        Object::Float(value) if *value == 0.0 || value.is_nan() => args[0].clone(),
+        Object::Float(value) => Reference::new(Object::Float(value.signum())),
+        _ => unreachable!(),
+    }
+};
+
+pub const EVEN: BuiltinFunction = |_, args| {
+    type_check!("even?", args, [Object::Integer(_)]);
+    unwrap_args!(args, [Object::Integer(value)]);
+
+    bool_from_native(value % 2 == 0)
+};
+
+pub const ODD: BuiltinFunction = |_, args| {
+    type_check!("odd?", args, [Object::Integer(_)]);
+    unwrap_args!(args, [Object::Integer(value)]);
+
+    bool_from_native(value % 2 != 0)
+};
+
+/// Integer division rounding towards negative infinity
+pub const DIV_FLOOR: BuiltinFunction = |_, args| {
+    let (l, r) = match integer_pair("div_floor", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    match div_floor(l, r) {
+        Ok(value) => Reference::new(Object::Integer(value)),
+        Err(err) => new_function_error("div_floor", err),
+    }
+};
+
+/// Remainder of `div_floor`, it has the same sign as the divisor
+pub const MOD_FLOOR: BuiltinFunction = |_, args| {
+    let (l, r) = match integer_pair("mod_floor", &args) {
+        Ok(pair) => pair,
+        Err(err) => return err,
+    };
+
+    if r == 0 {
+        return new_function_error("mod_floor", DIVISION_BY_ZERO);
+    }
+
+    // checked_rem only fails for isize::MIN % -1 where the remainder is 0
+    let remainder = l.checked_rem(r).unwrap_or(0);
+
+    if remainder != 0 && ((remainder < 0) != (r < 0)) {
+        Reference::new(Object::Integer(remainder + r))
+    } else {
+        Reference::new(Object::Integer(remainder))
+    }
+};
+
+/// Converts a number into a float
+pub const FLOAT: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("float", &args, 1);
+    }
+
+    match Number::from_object(&args[0]) {
+        Some(number) => Reference::new(Object::Float(number.as_f64())),
+        None => new_type_error_with_got_and_pos("float", 0, NUMERIC, args[0].type_of()),
+    }
+};
+
+/// Converts a float into a number, truncating it
+pub const INT: BuiltinFunction = |_, args| float_to_integer("int", &args, f64::trunc);
+
+pub const FLOOR: BuiltinFunction = |_, args| float_to_integer("floor", &args, f64::floor);
+
+pub const CEIL: BuiltinFunction = |_, args| float_to_integer("ceil", &args, f64::ceil);
+
+pub const ROUND: BuiltinFunction = |_, args| float_to_integer("round", &args, f64::round);
+
+pub const SIN: BuiltinFunction = |_, args| unary_float("sin", &args, f64::sin);
+
+pub const COS: BuiltinFunction = |_, args| unary_float("cos", &args, f64::cos);
+
+pub const TAN: BuiltinFunction = |_, args| unary_float("tan", &args, f64::tan);
+
+pub const ASIN: BuiltinFunction = |_, args| unary_float("asin", &args, f64::asin);
+
+pub const ACOS: BuiltinFunction = |_, args| unary_float("acos", &args, f64::acos);
+
+pub const ATAN: BuiltinFunction = |_, args| unary_float("atan", &args, f64::atan);
+
+/// Arc tangent of the first argument (y) divided by the second (x)
+pub const ATAN2: BuiltinFunction = |_, args| {
+    if args.len() != 2 {
+        return new_args_len_error("atan2", &args, 2);
+    }
+
+    match number_args("atan2", &args) {
+        Ok(numbers) => Reference::new(Object::Float(
+            numbers[0].as_f64().atan2(numbers[1].as_f64()),
+        )),
+        Err(err) => err,
+    }
+};
+
+pub const EXP: BuiltinFunction = |_, args| unary_float("exp", &args, f64::exp);
+
+/// Natural logarithm
+pub const LN: BuiltinFunction = |_, args| unary_float("ln", &args, f64::ln);
+
+pub const LOG2: BuiltinFunction = |_, args| unary_float("log2", &args, f64::log2);
+
+pub const LOG10: BuiltinFunction = |_, args| unary_float("log10", &args, f64::log10);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::{objects::BuiltinFunction, objects::Object, Program, Reference},
+        test::new_test_program,
+        utils::random::Rng,
+    };
+
+    use super::{ABS, ADD, DIVIDE, DIV_FLOOR, GCD, LCM, MOD, MOD_FLOOR, MULTIPLY, POW, SUBTRACT};
+
+    /// Operands close to the edges of isize are picked more often than the rest
+    fn random_operand(rng: &mut Rng) -> isize {
+        const EDGES: [isize; 7] = [0, 1, -1, 2, -2, isize::MIN, isize::MAX];
+
+        match rng.next_below(4) {
+            0 => EDGES[rng.next_below(EDGES.len() as u64) as usize],
+            1 => rng.next_below(200) as isize - 100,
+            _ => rng.next_u64() as isize,
+        }
+    }
+
+    fn gcd(l: i128, r: i128) -> i128 {
+        let (mut a, mut b) = (l.abs(), r.abs());
+
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+
+        a
+    }
+
+    /// Calls the function and checks it returns the expected integer, or an error when there is none
+    fn check_integer(
+        program: &mut Program,
+        function: BuiltinFunction,
+        call: String,
+        args: &[isize],
+        expected: Option<i128>,
+    ) {
+        let args = args
+            .iter()
+            .map(|arg| Reference::new(Object::Integer(*arg)))
+            .collect();
+        let expected = expected.and_then(|value| isize::try_from(value).ok());
+
+        match (function(program, args).as_ref(), expected) {
+            (Object::Integer(value), Some(expected)) => {
+                assert_eq!(*value, expected, "{call} returned a wrong value")
+            }
+            (Object::Error(_), None) => {}
+            (result, expected) => {
+                panic!("{call} returned {result} instead of {expected:?}")
+            }
+        }
+    }
+
+    #[test]
+    fn integer_arithmetic_is_total() {
+        type Checked = fn(i128, i128) -> Option<i128>;
+
+        let operations: [(&str, BuiltinFunction, Checked); 9] = [
+            ("+", ADD, |l, r| Some(l + r)),
+            ("-", SUBTRACT, |l, r| Some(l - r)),
+            ("*", MULTIPLY, |l, r| Some(l * r)),
+            ("/", DIVIDE, |l, r| l.checked_div(r)),
+            ("%", MOD, |l, r| l.checked_rem(r)),
+            ("div_floor", DIV_FLOOR, |l, r| {
+                (r != 0).then(|| l.div_euclid(r) - i128::from(r < 0 && l.rem_euclid(r) != 0))
+            }),
+            ("mod_floor", MOD_FLOOR, |l, r| {
+                (r != 0)
+                    .then(|| l - r * (l.div_euclid(r) - i128::from(r < 0 && l.rem_euclid(r) != 0)))
+            }),
+            ("gcd", GCD, |l, r| Some(gcd(l, r))),
+            ("lcm", LCM, |l, r| {
+                Some(match (l, r) {
+                    (0, _) | (_, 0) => 0,
+                    (l, r) => (l * r).abs() / gcd(l, r),
+                })
+            }),
+        ];
+
+        let mut program = new_test_program();
+        let mut rng = Rng::new(0x5eed);
+
+        for _ in 0..10_000 {
+            let l = random_operand(&mut rng);
+            let r = random_operand(&mut rng);
+
+            for (name, function, checked) in operations {
+                let expected = checked(l as i128, r as i128);
+                check_integer(
+                    &mut program,
+                    function,
+                    format!("({name} {l} {r})"),
+                    &[l, r],
+                    expected,
+                );
+            }
+
+            check_integer(
+                &mut program,
+                ABS,
+                format!("(abs {l})"),
+                &[l],
+                Some((l as i128).abs()),
+            );
+
+            // negative exponents return floats
+            let exp = rng.next_below(70) as isize;
+            check_integer(
+                &mut program,
+                POW,
+                format!("(pow {l} {exp})"),
+                &[l, exp],
+                (l as i128).checked_pow(exp as u32),
+            );
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Builtin functions for generating pseudo random values
+use crate::interpreter::{
+    objects::{BuiltinFunction, Object},
+    Env, Reference, LIST, NULL, NUMBER,
+};
+
+use super::{
+    add_std_builtins,
+    errors::{new_args_len_error, new_function_error, new_type_error_with_pos},
+};
+
+pub fn add_random_builtins(env: &mut Env) {
+    let functions: [(&str, BuiltinFunction); _] = [
+        ("seed", SEED),
+        ("random_int", RANDOM_INT),
+        ("random_float", RANDOM_FLOAT),
+        ("shuffle", SHUFFLE),
+        ("choice", CHOICE),
+        ("sample", SAMPLE),
+    ];
+
+    add_std_builtins(env, functions);
+}
+
+/// Seeds the random number generator of the program
+pub const SEED: BuiltinFunction = |program, args| {
+    if args.len() != 1 {
+        return new_args_len_error("seed", &args, 1);
+    }
+
+    let Object::Integer(seed) = args[0].as_ref() else {
+        return new_type_error_with_pos("seed", NUMBER.type_of(), 0);
+    };
+
+    program.set_seed(*seed as u64);
+
+    NULL.clone()
+};
+
+/// Returns a random integer between the first argument (inclusive) and the second (exclusive).
+///
+/// If a single argument is passed the range starts at 0
+pub const RANDOM_INT: BuiltinFunction = |program, args| {
+    let (low, high) = match args.iter().map(|v| v.as_ref()).collect::<Vec<_>>()[..] {
+        [Object::Integer(high)] => (0, *high),
+        [Object::Integer(low), Object::Integer(high)] => (*low, *high),
+        [_] | [_, _] => {
+            let pos = args
+                .iter()
+                .position(|v| !matches!(v.as_ref(), Object::Integer(_)))
+                .unwrap_or_default();
+
+            return new_type_error_with_pos("random_int", NUMBER.type_of(), pos);
+        }
+        _ => return new_args_len_error("random_int", &args, 2),
+    };
+
+    if low >= high {
+        return new_function_error("random_int", "the range is empty");
+    }
+
+    let range = high.abs_diff(low) as u64;
+    let value = low.wrapping_add(program.rng().next_below(range) as isize);
+
+    Reference::new(Object::Integer(value))
+};
+
+/// Returns a random float between 0 (inclusive) and 1 (exclusive)
+pub const RANDOM_FLOAT: BuiltinFunction = |program, args| {
+    if !args.is_empty() {
+        return new_args_len_error("random_float", &args, 0);
+    }
+
+    Reference::new(Object::Float(program.rng().next_f64()))
+};
+
+/// Returns a new list with the items in a random order
+pub const SHUFFLE: BuiltinFunction = |program, args| {
+    if args.len() != 1 {
+        return new_args_len_error("shuffle", &args, 1);
+    }
+
+    let Object::List(list) = args[0].as_ref() else {
+        return new_type_error_with_pos("shuffle", LIST.type_of(), 0);
+    };
+
+    let mut vec = list.iter().cloned().collect::<Vec<_>>();
+    program.rng().shuffle(&mut vec);
+
+    Reference::new(Object::List(vec.into()))
+};
+
+/// Returns a random item of a list. null if the list is empty
+pub const CHOICE: BuiltinFunction = |program, args| {
+    if args.len() != 1 {
+        return new_args_len_error("choice", &args, 1);
+    }
+
+    let Object::List(list) = args[0].as_ref() else {
+        return new_type_error_with_pos("choice", LIST.type_of(), 0);
+    };
+
+    if list.is_empty() {
+        return NULL.clone();
+    }
+
+    let idx = program.rng().next_below(list.len() as u64) as usize;
+
+    list[idx].clone()
+};
+
+/// Returns `n` random items of a list without repeating positions
+pub const SAMPLE: BuiltinFunction = |program, args| {
+    if args.len() != 2 {
+        return new_args_len_error("sample", &args, 2);
+    }
+
+    let Object::List(list) = args[0].as_ref() else {
+        return new_type_error_with_pos("sample", LIST.type_of(), 0);
+    };
+    let Object::Integer(n) = args[1].as_ref() else {
+        return new_type_error_with_pos("sample", NUMBER.type_of(), 1);
+    };
+
+    if *n < 0 || *n as usize > list.len() {
+        return new_function_error(
+            "sample",
+            format!("cannot take {} items from a list of {}", n, list.len()),
+        );
+    }
+
+    let n = *n as usize;
+    let mut vec = list.iter().cloned().collect::<Vec<_>>();
+    let rng = program.rng();
+
+    // Partial Fisher-Yates: the first `n` positions end up with the sample
+    for i in 0..n {
+        let j = i + rng.next_below((vec.len() - i) as u64) as usize;
+        vec.swap(i, j);
+    }
+
+    vec.truncate(n);
+
+    Reference::new(Object::List(vec.into()))
+};
@@ -0,0 +1,188 @@
+//! Builtin functions for creating and consuming lazy sequences
+use crate::interpreter::{
+    objects::{BuiltinFunction, Object, Sequence, Source},
+    Env, Program, Reference, FUNCTION, LIST, NUMBER,
+};
+
+use super::{
+    add_std_builtins,
+    errors::{new_args_len_error, new_function_error, new_type_error_with_pos},
+    type_check,
+};
+
+pub fn add_sequence_builtins(env: &mut Env) {
+    let functions: [(&str, BuiltinFunction); _] = [
+        ("lazy_range", LAZY_RANGE),
+        ("iterate", ITERATE),
+        ("repeat", REPEAT),
+        ("cycle", CYCLE),
+        ("collect", COLLECT),
+    ];
+
+    add_std_builtins(env, functions);
+}
+
+fn new_sequence(source: Source) -> Reference {
+    Reference::new(Object::Sequence(Sequence::new(source)))
+}
+
+/// Computes every item of a sequence
+pub(crate) fn collect_sequence(
+    program: &mut Program,
+    sequence: &Sequence,
+) -> Result<Vec<Reference>, Reference> {
+    let mut items = Vec::new();
+
+    while let Some(item) = sequence.get(program, items.len())? {
+        items.push(item);
+    }
+
+    Ok(items)
+}
+
+/// Lazy version of `map`, the function is called when the items are needed
+pub(super) fn lazy_map(sequence: &Sequence, function: &Reference) -> Reference {
+    if !function.is_callable() {
+        return new_type_error_with_pos("map", FUNCTION.type_of(), 1);
+    }
+
+    new_sequence(Source::Map {
+        sequence: sequence.clone(),
+        function: function.clone(),
+        idx: 0,
+    })
+}
+
+/// Lazy version of `filter`, the function is called when the items are needed
+pub(super) fn lazy_filter(sequence: &Sequence, function: &Reference) -> Reference {
+    if !function.is_callable() {
+        return new_type_error_with_pos("filter", FUNCTION.type_of(), 1);
+    }
+
+    new_sequence(Source::Filter {
+        sequence: sequence.clone(),
+        function: function.clone(),
+        idx: 0,
+    })
+}
+
+/// Lazy version of `take`
+pub(super) fn lazy_take(sequence: &Sequence, count: &Reference) -> Reference {
+    let Object::Integer(count) = count.as_ref() else {
+        return new_type_error_with_pos("take", NUMBER.type_of(), 1);
+    };
+
+    let Ok(remaining) = usize::try_from(*count) else {
+        return new_function_error("take", "the count cannot be negative");
+    };
+
+    new_sequence(Source::Take {
+        sequence: sequence.clone(),
+        remaining,
+        idx: 0,
+    })
+}
+
+/// Returns a lazy sequence of numbers from start (inclusive) to end (exclusive)
+///
+/// Without arguments the sequence starts at 0 and never ends, with one argument it starts at 0.
+/// Third argument is optional: the step between numbers
+pub const LAZY_RANGE: BuiltinFunction = |_, args| {
+    type_check!("lazy_range", args, Object::Integer(_));
+
+    let numbers = args
+        .iter()
+        .map(|arg| match arg.as_ref() {
+            Object::Integer(value) => *value,
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+
+    let (start, end, step) = match numbers[..] {
+        [] => (0, None, 1),
+        [end] => (0, Some(end), 1),
+        [start, end] => (start, Some(end), 1),
+        [start, end, step] => (start, Some(end), step),
+        _ => return new_args_len_error("lazy_range", &args, 2),
+    };
+
+    if step == 0 {
+        return new_function_error("lazy_range", "the step cannot be zero");
+    }
+
+    new_sequence(Source::Range {
+        next: Some(start),
+        end,
+        step,
+    })
+};
+
+/// Returns the infinite sequence of the initial value and the results of calling the function with the previous item
+pub const ITERATE: BuiltinFunction = |_, args| {
+    if args.len() != 2 {
+        return new_args_len_error("iterate", &args, 2);
+    }
+
+    if !args[1].is_callable() {
+        return new_type_error_with_pos("iterate", FUNCTION.type_of(), 1);
+    }
+
+    new_sequence(Source::Iterate {
+        value: args[0].clone(),
+        function: args[1].clone(),
+        started: false,
+    })
+};
+
+/// Returns a sequence that repeats the value
+///
+/// Second argument is optional: the amount of times, without it the sequence never ends
+pub const REPEAT: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 1 && len != 2 {
+        return new_args_len_error("repeat", &args, 1);
+    }
+
+    let remaining = match args.get(1).map(|arg| arg.as_ref()) {
+        None => None,
+        Some(Object::Integer(count)) => match usize::try_from(*count) {
+            Ok(count) => Some(count),
+            Err(_) => return new_function_error("repeat", "the count cannot be negative"),
+        },
+        Some(_) => return new_type_error_with_pos("repeat", NUMBER.type_of(), 1),
+    };
+
+    new_sequence(Source::Repeat {
+        value: args[0].clone(),
+        remaining,
+    })
+};
+
+/// Returns an infinite sequence that goes over the items of a list again and again
+pub const CYCLE: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("cycle", &args, 1);
+    }
+
+    let Object::List(items) = args[0].as_ref() else {
+        return new_type_error_with_pos("cycle", LIST.type_of(), 0);
+    };
+
+    new_sequence(Source::Cycle {
+        items: items.clone(),
+        idx: 0,
+    })
+};
+
+/// Computes the items of a sequence and returns them as a list. Lists are returned as they are
+pub const COLLECT: BuiltinFunction = |program, args| {
+    type_check!("collect", args, [Object::Sequence(_) | Object::List(_)]);
+
+    match args[0].as_ref() {
+        Object::Sequence(sequence) => match collect_sequence(program, sequence) {
+            Ok(items) => Reference::new(Object::List(items.into())),
+            Err(err) => err,
+        },
+        _ => args[0].clone(),
+    }
+};
@@ -1,12 +1,12 @@
 //! Builtin functions for string operations
-use std::sync::Arc;
 
 use crate::interpreter::{
     objects::{BuiltinFunction, Object},
-    Env, Reference, STRING,
+    Env, List, Reference, STRING,
 };
 
 use super::{
+    add_std_builtins,
     errors::{new_args_len_error, new_type_error_with_pos},
     type_check, typecheck_args,
 };
@@ -15,13 +15,7 @@ pub fn add_string_builtins(env: &mut Env) {
     let functions: [(&str, BuiltinFunction); _] =
         [("str", STR), ("lines", LINES), ("split", SPLIT)];
 
-    functions
-        .into_iter()
-        .map(|(name, function)| (name, Reference::new(Object::Builtin { function })))
-        .for_each(|(name, function)| {
-            env.insert(name.into(), function.clone());
-            env.insert(("std/".to_owned() + name).into(), function);
-        });
+    add_std_builtins(env, functions);
 }
 
 /// Concatenates the arguments into a string
@@ -65,7 +59,7 @@ pub const LINES: BuiltinFunction = |_, args| {
     let lines = inner
         .lines()
         .map(|v| Reference::new(Object::String(v.into())))
-        .collect::<Arc<_>>();
+        .collect::<List>();
 
     Reference::new(Object::List(lines))
 };
@@ -88,7 +82,7 @@ pub const SPLIT: BuiltinFunction = |_, args| {
     let list = input
         .split(split.as_ref())
         .map(|v| Reference::new(Object::String(v.into())))
-        .collect::<Arc<_>>();
+        .collect::<List>();
 
     Reference::new(Object::List(list))
 };
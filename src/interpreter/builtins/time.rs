@@ -0,0 +1,189 @@
+//! Builtin functions for clocks and dates
+use std::{
+    sync::LazyLock,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::interpreter::{
+    is_truthy,
+    objects::{BuiltinFunction, Object},
+    Env, Reference, NUMBER, STRING,
+};
+
+use super::{
+    add_std_builtins,
+    errors::{new_args_len_error, new_function_error, new_type_error_with_pos},
+};
+
+/// Instant used as the origin of `monotonic_ns`
+static START: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+pub fn add_time_builtins(env: &mut Env) {
+    let functions: [(&str, BuiltinFunction); _] = [
+        ("now_ms", NOW_MS),
+        ("monotonic_ns", MONOTONIC_NS),
+        ("elapsed", ELAPSED),
+        ("format_date", FORMAT_DATE),
+        ("parse_date", PARSE_DATE),
+        ("local_offset", LOCAL_OFFSET),
+    ];
+
+    add_std_builtins(env, functions);
+}
+
+fn monotonic_ns() -> isize {
+    START.elapsed().as_nanos() as isize
+}
+
+/// Returns the milliseconds since the unix epoch
+pub const NOW_MS: BuiltinFunction = |_, args| {
+    if !args.is_empty() {
+        return new_args_len_error("now_ms", &args, 0);
+    }
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as isize);
+
+    Reference::new(Object::Integer(millis))
+};
+
+/// Returns the nanoseconds of a monotonic clock. Only useful to measure the time between two calls
+pub const MONOTONIC_NS: BuiltinFunction = |_, args| {
+    if !args.is_empty() {
+        return new_args_len_error("monotonic_ns", &args, 0);
+    }
+
+    Reference::new(Object::Integer(monotonic_ns()))
+};
+
+/// Returns the nanoseconds elapsed since a value returned by `monotonic_ns`
+pub const ELAPSED: BuiltinFunction = |_, args| {
+    if args.len() != 1 {
+        return new_args_len_error("elapsed", &args, 1);
+    }
+
+    let Object::Integer(start) = args[0].as_ref() else {
+        return new_type_error_with_pos("elapsed", NUMBER.type_of(), 0);
+    };
+
+    match monotonic_ns().checked_sub(*start) {
+        Some(elapsed) => Reference::new(Object::Integer(elapsed)),
+        None => new_function_error(
+            "elapsed",
+            "the start should be a value returned by monotonic_ns",
+        ),
+    }
+};
+
+/// Formats milliseconds since the unix epoch with a strftime like format string.
+///
+/// Third argument is optional: if truthy the date is formatted in the local offset instead of UTC
+pub const FORMAT_DATE: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 2 && len != 3 {
+        return new_args_len_error("format_date", &args, 2);
+    }
+
+    let Object::Integer(millis) = args[0].as_ref() else {
+        return new_type_error_with_pos("format_date", NUMBER.type_of(), 0);
+    };
+    let Object::String(format) = args[1].as_ref() else {
+        return new_type_error_with_pos("format_date", STRING.type_of(), 1);
+    };
+
+    let Some(date) = DateTime::<Utc>::from_timestamp_millis(*millis as i64) else {
+        return new_function_error("format_date", "timestamp out of range");
+    };
+
+    let mut output = String::new();
+
+    let result = if args.get(2).is_some_and(is_truthy) {
+        std::fmt::write(
+            &mut output,
+            format_args!("{}", date.with_timezone(&Local).format(format)),
+        )
+    } else {
+        std::fmt::write(&mut output, format_args!("{}", date.format(format)))
+    };
+
+    match result {
+        Ok(_) => Reference::new(Object::String(output.into())),
+        Err(_) => new_function_error("format_date", "invalid format string"),
+    }
+};
+
+/// Parses a date with a strftime like format string and returns the milliseconds since the unix epoch.
+///
+/// Dates without an offset are read as UTC.
+/// Third argument is optional: if truthy dates without an offset are read in the local offset instead
+pub const PARSE_DATE: BuiltinFunction = |_, args| {
+    let len = args.len();
+    if len != 2 && len != 3 {
+        return new_args_len_error("parse_date", &args, 2);
+    }
+
+    let Object::String(input) = args[0].as_ref() else {
+        return new_type_error_with_pos("parse_date", STRING.type_of(), 0);
+    };
+    let Object::String(format) = args[1].as_ref() else {
+        return new_type_error_with_pos("parse_date", STRING.type_of(), 1);
+    };
+
+    if let Ok(date) = DateTime::parse_from_str(input, format) {
+        return Reference::new(Object::Integer(date.timestamp_millis() as isize));
+    }
+
+    let naive = match NaiveDateTime::parse_from_str(input, format) {
+        Ok(date) => date,
+        Err(err) => match NaiveDate::parse_from_str(input, format) {
+            Ok(date) => date.and_time(Default::default()),
+            Err(_) => return new_function_error("parse_date", err),
+        },
+    };
+
+    let millis = if args.get(2).is_some_and(is_truthy) {
+        match Local.from_local_datetime(&naive).earliest() {
+            Some(date) => date.timestamp_millis(),
+            None => return new_function_error("parse_date", "invalid local date"),
+        }
+    } else {
+        naive.and_utc().timestamp_millis()
+    };
+
+    Reference::new(Object::Integer(millis as isize))
+};
+
+/// Returns the offset of the local timezone from UTC in seconds
+pub const LOCAL_OFFSET: BuiltinFunction = |_, args| {
+    if !args.is_empty() {
+        return new_args_len_error("local_offset", &args, 0);
+    }
+
+    let offset = Local::now().offset().local_minus_utc();
+
+    Reference::new(Object::Integer(offset as isize))
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::{objects::Object, Reference},
+        test::new_test_program,
+    };
+
+    use super::ELAPSED;
+
+    #[test]
+    fn elapsed_since_an_invalid_start_is_an_error() {
+        let mut program = new_test_program();
+        let result = ELAPSED(
+            &mut program,
+            vec![Reference::new(Object::Integer(isize::MIN))],
+        );
+
+        assert!(matches!(result.as_ref(), Object::Error(_)), "{}", result);
+    }
+}
@@ -1,31 +1,63 @@
 use std::sync::{Arc, LazyLock};
 
-use crate::{ast::Node, lexer::Token};
+use crate::{
+    ast::{Node, Parameters},
+    lexer::Token,
+};
 
-use super::{objects::Object, EnvReference, Reference};
+use super::{
+    objects::{Arity, Atom, Namespace, Object, Sequence, Source},
+    EnvReference, List, Map, Reference,
+};
 
 pub static NULL: LazyLock<Reference> = LazyLock::new(|| Reference::new(Object::Null));
 pub static TRUE: LazyLock<Reference> = LazyLock::new(|| Reference::new(Object::Bool(true)));
 pub static FALSE: LazyLock<Reference> = LazyLock::new(|| Reference::new(Object::Bool(false)));
 pub static NUMBER: LazyLock<Reference> = LazyLock::new(|| Reference::new(Object::Integer(0)));
+pub static FLOAT: LazyLock<Reference> = LazyLock::new(|| Reference::new(Object::Float(0.0)));
 pub static STRING: LazyLock<Reference> =
     LazyLock::new(|| Reference::new(Object::String(String::new().into())));
-pub static LIST: LazyLock<Reference> = LazyLock::new(|| Reference::new(Object::List(Arc::new([]))));
+pub static LIST: LazyLock<Reference> = LazyLock::new(|| Reference::new(Object::List(List::new())));
+pub static MAP: LazyLock<Reference> =
+    LazyLock::new(|| Reference::new(Object::Map(Arc::new(Map::new()))));
 pub static FUNCTION: LazyLock<Reference> = LazyLock::new(|| {
     Reference::new(Object::Function {
-        body: Node::Invalid(Token::default()),
-        parameters: Arc::default(),
+        name: None,
+        doc: None,
+        arities: [Arity {
+            parameters: Parameters::default(),
+            body: Node::Invalid(Token::default()),
+        }]
+        .into(),
         env: EnvReference::default(),
     })
 });
 
-pub static ALL_TYPES: LazyLock<[Reference; 6]> = LazyLock::new(|| {
+pub static SEQUENCE: LazyLock<Reference> = LazyLock::new(|| {
+    Reference::new(Object::Sequence(Sequence::new(Source::Repeat {
+        value: NULL.clone(),
+        remaining: Some(0),
+    })))
+});
+
+pub static ATOM: LazyLock<Reference> =
+    LazyLock::new(|| Reference::new(Object::Atom(Atom::new(NULL.clone()))));
+
+pub static NAMESPACE: LazyLock<Reference> =
+    LazyLock::new(|| Reference::new(Object::Namespace(Namespace::new(""))));
+
+pub static ALL_TYPES: LazyLock<[Reference; 11]> = LazyLock::new(|| {
     [
         NULL.clone(),
         TRUE.clone(),
         NUMBER.clone(),
+        FLOAT.clone(),
         STRING.clone(),
         LIST.clone(),
+        MAP.clone(),
         FUNCTION.clone(),
+        SEQUENCE.clone(),
+        ATOM.clone(),
+        NAMESPACE.clone(),
     ]
 });
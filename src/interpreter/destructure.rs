@@ -0,0 +1,82 @@
+//! Splits values into the names of a destructuring pattern
+use std::sync::Arc;
+
+use crate::ast::Pattern;
+
+use super::{objects::Object, Reference};
+
+/// Adds the names of the pattern with their part of the value to the bindings.
+///
+/// Returns a message describing the mismatch when the value does not have the shape of the pattern
+pub(crate) fn destructure(
+    pattern: &Pattern,
+    value: Reference,
+    bindings: &mut impl Extend<(Arc<str>, Reference)>,
+) -> Result<(), String> {
+    match pattern {
+        Pattern::Word(name) => {
+            bindings.extend([(name.clone(), value)]);
+
+            Ok(())
+        }
+        Pattern::List { items, rest } => {
+            let Object::List(list) = value.as_ref() else {
+                return Err(format!(
+                    "cannot destructure {} with the list pattern {}",
+                    value.type_of(),
+                    pattern
+                ));
+            };
+
+            let matches_len = match rest {
+                Some(_) => list.len() >= items.len(),
+                None => list.len() == items.len(),
+            };
+
+            if !matches_len {
+                return Err(format!(
+                    "the list pattern {} expected {}{} items got {}",
+                    pattern,
+                    if rest.is_some() { "at least " } else { "" },
+                    items.len(),
+                    list.len()
+                ));
+            }
+
+            for (item, value) in items.iter().zip(list.iter()) {
+                destructure(item, value.clone(), bindings)?;
+            }
+
+            match rest {
+                Some(rest) => destructure(
+                    rest,
+                    Reference::new(Object::List(list.skip(items.len()))),
+                    bindings,
+                ),
+                None => Ok(()),
+            }
+        }
+        Pattern::Keys(keys) => {
+            let Object::Map(map) = value.as_ref() else {
+                return Err(format!(
+                    "cannot destructure {} with the map pattern {}",
+                    value.type_of(),
+                    pattern
+                ));
+            };
+
+            for key in keys {
+                let Some(value) = map.get(key) else {
+                    return Err(format!(
+                        "the map pattern {} is missing the key \"{}\"",
+                        pattern, key
+                    ));
+                };
+
+                bindings.extend([(key.clone(), value.clone())]);
+            }
+
+            Ok(())
+        }
+    }
+}
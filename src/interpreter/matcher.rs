@@ -0,0 +1,97 @@
+//! Matching values against the patterns of the `match` special form
+use std::sync::Arc;
+
+use crate::ast::{MatchLiteral, MatchPattern};
+
+use super::{objects::Object, Reference};
+
+impl MatchLiteral {
+    fn matches(&self, value: &Object) -> bool {
+        match (self, value) {
+            (MatchLiteral::Null, Object::Null) => true,
+            (MatchLiteral::Integer(literal), Object::Integer(value)) => literal == value,
+            (MatchLiteral::Float(literal), Object::Float(value)) => {
+                literal.total_cmp(value).is_eq()
+            }
+            (MatchLiteral::String(literal), Object::String(value)) => literal == value,
+            (MatchLiteral::Bool(literal), Object::Bool(value)) => literal == value,
+            _ => false,
+        }
+    }
+}
+
+impl MatchPattern {
+    /// Tests the value against the pattern, adding the bound names to the bindings
+    pub fn matches(&self, value: &Reference, bindings: &mut Vec<(Arc<str>, Reference)>) -> bool {
+        match self {
+            MatchPattern::Wildcard => true,
+            MatchPattern::Bind(name) => {
+                bindings.push((name.clone(), value.clone()));
+                true
+            }
+            MatchPattern::Literal(literal) => literal.matches(value),
+            MatchPattern::Type { name, pattern } => {
+                value.type_of() == name.as_ref()
+                    && pattern
+                        .as_ref()
+                        .is_none_or(|pattern| pattern.matches(value, bindings))
+            }
+            MatchPattern::List { items, rest } => {
+                let Object::List(list) = value.as_ref() else {
+                    return false;
+                };
+
+                let matches_len = match rest {
+                    Some(_) => list.len() >= items.len(),
+                    None => list.len() == items.len(),
+                };
+
+                matches_len
+                    && items
+                        .iter()
+                        .zip(list.iter())
+                        .all(|(item, value)| item.matches(value, bindings))
+                    && rest.as_ref().is_none_or(|rest| {
+                        rest.matches(
+                            &Reference::new(Object::List(list.skip(items.len()))),
+                            bindings,
+                        )
+                    })
+            }
+            MatchPattern::Keys(keys) => {
+                let Object::Map(map) = value.as_ref() else {
+                    return false;
+                };
+
+                keys.iter().all(|key| match map.get(key) {
+                    Some(value) => {
+                        bindings.push((key.clone(), value.clone()));
+                        true
+                    }
+                    None => false,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::objects::Object,
+        test::{new_test_program, prepare_code},
+    };
+
+    #[test]
+    fn match_without_a_matching_arm_is_an_error() {
+        let mut program = new_test_program();
+        let code = prepare_code(r#"(match 5 1 "one" "5" "five")"#.to_owned()).unwrap();
+        let result = program.eval(&code).unwrap();
+
+        let Object::Error(err) = result.as_ref() else {
+            panic!("expected an error got {}", result);
+        };
+
+        assert_eq!(err.as_ref(), "No pattern of match matched the value 5");
+    }
+}
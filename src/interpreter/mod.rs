@@ -1,15 +1,28 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, path::Path, sync::Arc};
 
 use anyhow::{anyhow, Context, Result};
 use foldhash::{HashMap, HashMapExt};
-use objects::Object;
+use objects::{describe_arities, select_arity, split_qualified, Arity, Namespace, Object};
 use once_cell::sync::Lazy;
 use parking_lot::{Mutex, RwLock};
 
-use crate::ast::Node;
+use crate::{
+    ast::{function_clause, function_clauses, FunctionClause, MatchArm, Node, Parameters, Pattern},
+    lexer::Token,
+    utils::random::Rng,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
 
 mod constants;
+mod destructure;
+mod matcher;
+pub mod modules;
+pub mod namespaces;
+pub mod prelude;
 pub use constants::*;
+use destructure::destructure;
+use modules::{Modules, Visibility};
 
 pub mod builtins;
 pub mod objects;
@@ -18,6 +31,9 @@ pub type Reference = Arc<Object>;
 pub type EnvReference = Arc<EnvReferenceInner>;
 pub type EnvReferenceInner = RwLock<Env>;
 pub type Env = HashMap<Arc<str>, Reference>;
+pub type Map = BTreeMap<Arc<str>, Reference>;
+/// Persistent vector, clones share their structure and updates only copy the changed path
+pub type List = imbl::Vector<Reference>;
 
 static NUMBER_LOOKUP_TABLE: Lazy<Mutex<HashMap<isize, Reference>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
@@ -34,13 +50,28 @@ macro_rules! map_rust_error {
     };
 }
 
+#[cfg(feature = "bin")]
 pub(crate) use map_rust_error;
 
 #[derive(Debug, Clone)]
 pub struct Program {
     env: CallStack,
+    /// Disables the builtins that can run other programs
+    sandboxed: bool,
+    rng: Rng,
+    /// Imported modules, shared with the programs of the modules
+    modules: Arc<Mutex<Modules>>,
+    /// Globals the program started with, modules are evaluated with them
+    base_env: Arc<Env>,
+    /// Names the program exports or keeps private when it is imported as a module
+    visibility: Visibility,
+    /// Namespace selected with `in-ns`, None for the user namespace
+    namespace: Option<Namespace>,
 }
 
+/// Seed of the random number generator of new programs
+pub const DEFAULT_SEED: u64 = 0;
+
 const STACK_SIZE: usize = 1024;
 
 #[derive(Debug, Clone)]
@@ -103,6 +134,12 @@ impl Program {
     }
 
     fn set_value(&mut self, name: Arc<str>, value: Reference) {
+        // top level definitions go into the namespace selected with in-ns
+        if let Some(namespace) = self.namespace.as_ref().filter(|_| self.env.sp == 0) {
+            namespace.insert(name, value);
+            return;
+        }
+
         let mut env = self.current_env_mut().write();
         env.insert(name, value);
     }
@@ -111,39 +148,247 @@ impl Program {
         &mut self,
         closure: &EnvReference,
         body: &Node,
-        parameters: &Arc<[Arc<str>]>,
-        args: &[Reference],
+        parameters: &Parameters,
+        args: Vec<Reference>,
     ) -> anyhow::Result<Reference> {
         //TODO: When calling a function multiple times with the same parameters, the environment should be cloned only once
         let mut env = closure.read().clone();
+        let mut args = args.into_iter();
 
-        env.extend(parameters.iter().cloned().zip(args.iter().cloned()));
+        for (pattern, arg) in parameters.required.iter().zip(args.by_ref()) {
+            if let Err(err) = destructure(pattern, arg, &mut env) {
+                return Ok(new_pattern_error(parameters, err));
+            }
+        }
 
         self.env
             .push_env(EnvReference::new(EnvReferenceInner::new(env)));
-        let result = self.eval(body);
+        let result =
+            self.bind_optional_parameters(parameters, args)
+                .and_then(|bound| match bound {
+                    Ok(()) => self.eval(body),
+                    Err(err) => Ok(err),
+                });
         self.env.pop_env();
 
         result
     }
 
+    /// Binds the optional and rest parameters in the current environment.
+    ///
+    /// Defaults are evaluated in order so they can refer to the previous parameters
+    fn bind_optional_parameters(
+        &mut self,
+        parameters: &Parameters,
+        mut args: impl Iterator<Item = Reference>,
+    ) -> anyhow::Result<Result<(), Reference>> {
+        for optional in parameters.optional.iter() {
+            let value = match args.next() {
+                Some(value) => value,
+                None => self
+                    .parse_expression(&optional.default)
+                    .and_then(map_rust_error!("default value error"))?,
+            };
+
+            if let Err(err) = self.bind(&optional.pattern, value) {
+                return Ok(Err(new_pattern_error(parameters, err)));
+            }
+        }
+
+        if let Some(rest) = &parameters.rest {
+            let rest_args = Reference::new(Object::List(args.collect()));
+
+            if let Err(err) = self.bind(rest, rest_args) {
+                return Ok(Err(new_pattern_error(parameters, err)));
+            }
+        }
+
+        Ok(Ok(()))
+    }
+
+    /// Destructures the value into the current environment
+    fn bind(&mut self, pattern: &Pattern, value: Reference) -> Result<(), String> {
+        let mut bindings = Vec::new();
+
+        destructure(pattern, value, &mut bindings)?;
+
+        for (name, value) in bindings {
+            self.set_value(name, value);
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates the body of the first arm whose pattern and guard match the value
+    fn match_value(&mut self, arms: &[MatchArm], value: &Reference) -> Result<Reference> {
+        for arm in arms {
+            let mut bindings = Vec::new();
+
+            if !arm.pattern.matches(value, &mut bindings) {
+                continue;
+            }
+
+            let mut env = Env::with_capacity(bindings.len());
+            env.extend(bindings);
+
+            self.env
+                .push_env(EnvReference::new(EnvReferenceInner::new(env)));
+            let result = match &arm.guard {
+                Some(guard) => self
+                    .parse_expression(guard)
+                    .and_then(map_rust_error!("match guard error"))
+                    .map(|condition| is_truthy(&condition)),
+                None => Ok(true),
+            }
+            .and_then(|matched| match matched {
+                true => self.eval(&arm.body).map(Some),
+                false => Ok(None),
+            });
+            self.env.pop_env();
+
+            if let Some(result) = result? {
+                return Ok(result);
+            }
+        }
+
+        Ok(Reference::new(Object::Error(
+            format!("No pattern of match matched the value {}", value).into(),
+        )))
+    }
+
+    /// Evaluates the values of a `let` in order, each value can use the names bound before it
+    fn bind_let(&mut self, bindings: &[(Pattern, &Node)]) -> Result<Result<(), Reference>> {
+        for (pattern, node) in bindings {
+            let value = self
+                .parse_expression(node)
+                .and_then(map_rust_error!("let value error"))?;
+
+            if let Err(err) = self.bind(pattern, value) {
+                return Ok(Err(Reference::new(Object::Error(
+                    format!("Invalid value for 'let': {}", err).into(),
+                ))));
+            }
+        }
+
+        Ok(Ok(()))
+    }
+
+    /// Replaces the value in the closest environment where the name is defined.
+    ///
+    /// Returns false when the name is not defined
+    fn update_value(&mut self, name: &str, value: Reference) -> bool {
+        let (globals, locals) = self.env.active_slice().split_first().unwrap();
+
+        for env in locals.iter().rev() {
+            let mut env = env.write();
+
+            if let Some(current) = env.get_mut(name) {
+                *current = value;
+                return true;
+            }
+        }
+
+        if let Some(namespace) = &self.namespace {
+            if namespace.binding(name).is_some() {
+                namespace.insert(name, value);
+                return true;
+            }
+        }
+
+        if let Some(current) = globals.write().get_mut(name) {
+            *current = value;
+            return true;
+        }
+
+        false
+    }
+
+    /// Returns the value of the name, looking in the local environments, the namespace selected with `in-ns`,
+    /// the globals and then the namespaces for names like `namespace/name`
     fn get_value(&mut self, name: &str) -> Reference {
-        for env in self.env.active_slice().iter().rev() {
+        let (globals, locals) = self.env.active_slice().split_first().unwrap();
+
+        for env in locals.iter().rev() {
             if let Some(value) = env.read().get(name) {
                 return value.clone();
             }
         }
 
-        NULL.clone()
+        if let Some(value) = self
+            .namespace
+            .as_ref()
+            .and_then(|namespace| namespace.binding(name))
+        {
+            return value;
+        }
+
+        if let Some(value) = globals.read().get(name) {
+            return value.clone();
+        }
+
+        self.get_qualified(name).unwrap_or_else(|| NULL.clone())
+    }
+
+    /// Resolves a name like `namespace/name` through the namespace defined with that name.
+    ///
+    /// Private names of an imported module resolve to an error
+    fn get_qualified(&mut self, name: &str) -> Option<Reference> {
+        let (namespace, rest) = split_qualified(name)?;
+
+        match self.get_value(namespace).as_ref() {
+            Object::Namespace(namespace) => namespace.resolve(rest).or_else(|| {
+                namespace.is_private(rest).then(|| {
+                    Reference::new(Object::Error(
+                        format!(
+                            "Cannot access {} from {}: it is private",
+                            rest,
+                            namespace.name()
+                        )
+                        .into(),
+                    ))
+                })
+            }),
+            _ => None,
+        }
     }
 
     pub fn new(global_env: Env) -> Self {
         Self {
+            base_env: Arc::new(global_env.clone()),
             env: CallStack::new(EnvReference::new(EnvReferenceInner::new(global_env))),
+            sandboxed: false,
+            rng: Rng::new(DEFAULT_SEED),
+            modules: Arc::default(),
+            visibility: Visibility::default(),
+            namespace: None,
         }
     }
 
-    pub fn call_expression(&mut self, nodes: &[Node]) -> anyhow::Result<Reference> {
+    /// Sets the file being run, relative imports are resolved from its directory
+    pub fn set_script_path(&mut self, path: impl AsRef<Path>) {
+        self.modules.lock().set_script_path(path.as_ref());
+    }
+
+    /// Resets the random number generator used by the random builtins
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    pub fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    /// Returns true if the builtins that can run other programs are disabled
+    pub fn sandboxed(&self) -> bool {
+        self.sandboxed
+    }
+
+    /// Enables or disables the builtins that can run other programs, for running untrusted code
+    pub fn set_sandboxed(&mut self, sandboxed: bool) {
+        self.sandboxed = sandboxed;
+    }
+
+    pub fn call_expression(&mut self, nodes: &Arc<[Node]>) -> anyhow::Result<Reference> {
         if nodes.is_empty() {
             return Ok(NULL.clone());
         }
@@ -152,7 +397,7 @@ impl Program {
 
         if let Node::Word(word) = &nodes[0] {
             match word.value.as_ref() {
-                "define" | "def" => {
+                "define" | "def" | "def-" => {
                     if len != 3 {
                         return Ok(Reference::new(Object::Error(
                             format!(
@@ -163,24 +408,152 @@ impl Program {
                         )));
                     }
 
-                    let name = match &nodes[1] {
-                        Node::Word(token) => token,
-                        n => {
-                            return Ok(Reference::new(Object::Error(
-                                format!("Invalid token for define: {:?} should be a word", n)
-                                    .into(),
-                            )))
+                    let value = match (&nodes[1], &nodes[2]) {
+                        // functions remember the name they were defined with
+                        (
+                            Node::Word(name),
+                            Node::FunctionLiteral {
+                                token: _,
+                                doc,
+                                clauses,
+                            },
+                        ) => new_function(Some(name.value.clone()), doc.as_ref(), clauses),
+                        (_, value) => self
+                            .parse_expression(value)
+                            .and_then(map_rust_error!("define value error"))?,
+                    };
+
+                    match &nodes[1] {
+                        Node::Word(name) => self.set_value(name.value.clone(), value),
+                        pattern => {
+                            let pattern = Pattern::from_node(pattern)
+                                .context("Invalid pattern for define")?;
+
+                            if let Err(err) = self.bind(&pattern, value) {
+                                return Ok(Reference::new(Object::Error(
+                                    format!("Invalid value for define: {}", err).into(),
+                                )));
+                            }
                         }
+                    }
+
+                    if word.value.as_ref() == "def-" {
+                        self.mark_private(&nodes[1]);
+                    }
+
+                    return Ok(NULL.clone());
+                }
+                "set!" => {
+                    if len != 3 {
+                        return Ok(Reference::new(Object::Error(
+                            format!("Invalid amount of arguments to 'set!' got: {}", len).into(),
+                        )));
+                    }
+
+                    let Node::Word(name) = &nodes[1] else {
+                        return Ok(Reference::new(Object::Error(
+                            "Invalid name for 'set!': should be a word".into(),
+                        )));
                     };
 
                     let value = self
                         .parse_expression(&nodes[2])
-                        .and_then(map_rust_error!("define value error"))?;
+                        .and_then(map_rust_error!("set! value error"))?;
+
+                    if !self.update_value(&name.value, value.clone()) {
+                        return Ok(Reference::new(Object::Error(
+                            format!("Cannot set! {}: it is not defined", name.value).into(),
+                        )));
+                    }
+
+                    return Ok(value);
+                }
+                "import" => return self.import(nodes),
+                "export" => return self.export(nodes),
+                "in-ns" => return self.in_namespace(nodes),
+                "refer" => return self.refer(nodes),
+                "defn" | "defn-" => {
+                    let Some(Node::Word(name)) = nodes.get(1) else {
+                        return Ok(Reference::new(Object::Error(
+                            "Invalid name for defn: should be a word".into(),
+                        )));
+                    };
+
+                    let (doc, rest) = match &nodes[2..] {
+                        [Node::StringLiteral(doc), rest @ ..] => (Some(doc), rest),
+                        rest => (None, rest),
+                    };
+
+                    let clauses = match rest {
+                        [Node::List(words), body] => vec![function_clause(words, body.clone())?],
+                        [Node::Expression(clauses)]
+                            if matches!(clauses.first(), Some(Node::List(_))) =>
+                        {
+                            function_clauses(clauses)?
+                        }
+                        _ => {
+                            return Ok(Reference::new(Object::Error(
+                                format!(
+                                    "Invalid arguments to defn {}: expected the parameters and the body",
+                                    name.value
+                                )
+                                .into(),
+                            )))
+                        }
+                    };
 
-                    self.set_value(name.value.clone(), value);
+                    let function = new_function(Some(name.value.clone()), doc, &clauses);
+
+                    self.set_value(name.value.clone(), function);
+
+                    if word.value.as_ref() == "defn-" {
+                        self.mark_private(&nodes[1]);
+                    }
 
                     return Ok(NULL.clone());
                 }
+                "let" => {
+                    if len != 3 {
+                        return Ok(Reference::new(Object::Error(
+                            format!("Invalid amount of arguments to 'let' got: {}", len).into(),
+                        )));
+                    }
+
+                    let Node::List(bindings) = &nodes[1] else {
+                        return Ok(Reference::new(Object::Error(
+                            "Invalid bindings for 'let': should be a list".into(),
+                        )));
+                    };
+
+                    if !bindings.len().is_multiple_of(2) {
+                        return Ok(Reference::new(Object::Error(
+                            "Invalid bindings for 'let': every pattern needs a value".into(),
+                        )));
+                    }
+
+                    let bindings = bindings
+                        .chunks(2)
+                        .map(|pair| Ok((Pattern::from_node(&pair[0])?, &pair[1])))
+                        .collect::<Result<Vec<_>>>()
+                        .context("Invalid pattern for 'let'")?;
+
+                    self.env.push_env(EnvReference::new(EnvReferenceInner::new(
+                        Env::with_capacity(bindings.len()),
+                    )));
+                    let result = self.bind_let(&bindings).and_then(|bound| match bound {
+                        Ok(()) => self.eval(&nodes[2]),
+                        Err(err) => Ok(err),
+                    });
+                    self.env.pop_env();
+
+                    return result;
+                }
+                // match expressions with a value are parsed as `Node::Match`
+                "match" => {
+                    return Ok(Reference::new(Object::Error(
+                        format!("Invalid amount of arguments to 'match' got: {}", len).into(),
+                    )));
+                }
                 "if" => {
                     if len != 4 && len != 3 {
                         return Ok(Reference::new(Object::Error(
@@ -212,6 +585,28 @@ impl Program {
 
                     return self.eval(&nodes[1]);
                 }
+                #[cfg(not(target_arch = "wasm32"))]
+                "time" => {
+                    if len != 2 {
+                        return Ok(Reference::new(Object::Error(
+                            format!("Invalid amount of arguments to 'time' got: {}", len).into(),
+                        )));
+                    }
+
+                    let position = nodes[1].first_char();
+                    let start = Instant::now();
+                    let result = self.parse_expression(&nodes[1]);
+
+                    // stderr keeps the timing out of the output of the program
+                    eprintln!(
+                        "time {}:{}: {:?}",
+                        position.line,
+                        position.col,
+                        start.elapsed()
+                    );
+
+                    return result;
+                }
                 _ => {}
             }
         }
@@ -230,8 +625,8 @@ impl Program {
             .collect::<Result<Vec<_>>>()?;
 
         match first.as_ref() {
-            Object::Builtin { function } => {
-                let r = function(self, args);
+            Object::Builtin { .. } | Object::Closure(_) => {
+                let r = self.call(&first, args)?;
 
                 if is_error(&r) {
                     return Err(anyhow!("error in builtin function: {}", r));
@@ -239,25 +634,36 @@ impl Program {
 
                 Ok(r)
             }
+            Object::Null => Ok(first),
+            _ => self.call(&first, args),
+        }
+    }
+
+    /// Calls a function, builtin or closure with the arguments.
+    ///
+    /// Errors of the callable are returned as error objects, `Err` is only returned when evaluating a function fails
+    pub fn call(&mut self, function: &Reference, args: Vec<Reference>) -> Result<Reference> {
+        match function.as_ref() {
+            Object::Builtin { function } => Ok(function(self, args)),
             Object::Function {
-                env,
-                parameters,
-                body,
+                env, name, arities, ..
             } => {
-                if args.len() != parameters.len() {
+                let Some(arity) = select_arity(arities, args.len()) else {
                     return Ok(Reference::new(Object::Error(
                         format!(
-                            "Invalid number of arguments passed into function got {} expected {}",
+                            "Invalid number of arguments passed into function {}{} got {} expected {}",
+                            name.as_ref().map(|name| format!("{} ", name)).unwrap_or_default(),
+                            describe_arities(arities, " | ", Parameters::to_string),
                             args.len(),
-                            parameters.len()
+                            describe_arities(arities, " or ", Parameters::arity)
                         )
                         .into(),
                     )));
-                }
+                };
 
-                self.run_function(env, body, parameters, &args)
+                self.run_function(env, &arity.body, &arity.parameters, args)
             }
-            Object::Null => Ok(first),
+            Object::Closure(closure) => closure.call(self, args),
             obj => Ok(Reference::new(Object::Error(
                 format!("Cannot call value of type {}", obj.type_of()).into(),
             ))),
@@ -342,6 +748,7 @@ impl Program {
                     Ok(obj)
                 }
             }
+            Node::FloatLiteral { value, .. } => Ok(Reference::new(Object::Float(*value))),
             Node::Invalid(_) => Ok(Reference::new(Object::Error(
                 "Evaluating Invalid Node".into(),
             ))),
@@ -353,38 +760,54 @@ impl Program {
                         self.parse_expression(item)
                             .and_then(map_rust_error!("list element"))
                     })
-                    .collect::<Result<Arc<[_]>>>()?;
+                    .collect::<Result<List>>()?;
 
                 Ok(Reference::new(Object::List(items)))
             }
             Node::FunctionLiteral {
                 token: _,
-                arguments,
-                body,
-            } => {
-                let arguments = arguments
-                    .iter()
-                    .map(|arg| {
-                        let Node::Word(token) = arg else {
-                            return Err(anyhow!("argument is not a word"));
-                        };
-
-                        Ok(token.value.clone())
-                    })
-                    .collect::<Result<Arc<[_]>>>()?;
-
-                let env = EnvReference::new(EnvReferenceInner::new(Env::with_capacity(16)));
-
-                Ok(Reference::new(Object::Function {
-                    env,
-                    parameters: arguments,
-                    body: (**body).clone(),
-                }))
+                doc,
+                clauses,
+            } => Ok(new_function(None, doc.as_ref(), clauses)),
+            Node::Match { value, arms, .. } => {
+                let value = self
+                    .parse_expression(value)
+                    .and_then(map_rust_error!("match value error"))?;
+
+                self.match_value(arms, &value)
             }
         }
     }
 }
 
+/// Builds a function from the clauses of a function literal
+fn new_function(
+    name: Option<Arc<str>>,
+    doc: Option<&Token>,
+    clauses: &[FunctionClause],
+) -> Reference {
+    let arities = clauses
+        .iter()
+        .map(|clause| Arity {
+            parameters: clause.parameters.clone(),
+            body: (*clause.body).clone(),
+        })
+        .collect::<Arc<[_]>>();
+
+    let env = EnvReference::new(EnvReferenceInner::new(Env::with_capacity(16)));
+
+    Reference::new(Object::Function {
+        env,
+        name,
+        doc: doc.map(|doc| {
+            let len = doc.value.len();
+
+            doc.value[1..(len - 1)].into()
+        }),
+        arities,
+    })
+}
+
 fn bool_from_native(value: bool) -> Reference {
     if value {
         TRUE.clone()
@@ -398,6 +821,17 @@ pub fn is_error(value: &Reference) -> bool {
     matches!(value.as_ref(), Object::Error(_))
 }
 
+/// Error returned when an argument does not match the pattern of its parameter
+fn new_pattern_error(parameters: &Parameters, err: String) -> Reference {
+    Reference::new(Object::Error(
+        format!(
+            "Invalid arguments passed into function {}: {}",
+            parameters, err
+        )
+        .into(),
+    ))
+}
+
 fn is_truthy(value: &Reference) -> bool {
     match value.as_ref() {
         Object::Integer(v) => {
@@ -405,6 +839,11 @@ fn is_truthy(value: &Reference) -> bool {
                 return true;
             }
         }
+        Object::Float(v) => {
+            if v != &0.0 {
+                return true;
+            }
+        }
         Object::String(v) => {
             if !v.is_empty() {
                 return true;
@@ -413,10 +852,11 @@ fn is_truthy(value: &Reference) -> bool {
         Object::Bool(v) => {
             return *v;
         }
-        Object::List(vec) => {
-            if !vec.is_empty() {
-                return true;
-            }
+        Object::List(vec) if !vec.is_empty() => {
+            return true;
+        }
+        Object::Map(map) if !map.is_empty() => {
+            return true;
         }
         _ => {}
     }
@@ -0,0 +1,520 @@
+//! Loading of other `.alc` files with `import`
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{
+    ast::{Node, Pattern, AST},
+    lexer::Lexer,
+};
+
+use super::{
+    is_error,
+    objects::{with_env, Namespace, Object},
+    CallStack, Env, EnvReference, EnvReferenceInner, Program, Reference, NULL,
+};
+
+/// Environment variable with the directories searched for modules, separated like `PATH`
+pub const SEARCH_PATH_VAR: &str = "ALC_PATH";
+/// Extension added to modules imported by name: `(import lib)` loads `lib.alc`
+pub const MODULE_EXTENSION: &str = "alc";
+
+/// Option of import that sets the prefix of the definitions
+pub const AS_OPTION: &str = ":as";
+/// Option of import that only defines the listed names
+pub const ONLY_OPTION: &str = ":only";
+
+/// Top level definitions of an evaluated module
+#[derive(Debug, Default)]
+pub struct Module {
+    /// Definitions visible to importers, sorted by name
+    pub exports: Vec<(Arc<str>, Reference)>,
+    /// Names defined by the module that importers cannot see
+    pub private: Vec<Arc<str>>,
+}
+
+impl Module {
+    pub fn get(&self, name: &str) -> Option<&Reference> {
+        self.exports
+            .iter()
+            .find(|(export, _)| export.as_ref() == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn is_private(&self, name: &str) -> bool {
+        self.private.iter().any(|private| private.as_ref() == name)
+    }
+}
+
+/// Names a file chose to expose with `export`, `def-` and `defn-`
+#[derive(Debug, Clone, Default)]
+pub struct Visibility {
+    /// Names listed by `export`, when there is any only they are visible to importers
+    exported: Option<BTreeSet<Arc<str>>>,
+    /// Names defined with `def-` or `defn-`
+    private: BTreeSet<Arc<str>>,
+    /// Prefixes of the namespaces created by `import`, they are only visible if `export` lists them
+    imported: BTreeSet<Arc<str>>,
+}
+
+impl Visibility {
+    pub fn is_public(&self, name: &str) -> bool {
+        !self.private.contains(name)
+            && match &self.exported {
+                Some(exported) => exported.contains(name),
+                None => !self.imported.contains(name),
+            }
+    }
+}
+
+/// Modules shared by a program and the modules it imports
+#[derive(Debug, Default)]
+pub struct Modules {
+    /// Definitions of the modules that were already evaluated, by their canonical path
+    cache: BTreeMap<PathBuf, Arc<Module>>,
+    /// Files being evaluated, the last one is the file being run right now
+    loading: Vec<PathBuf>,
+}
+
+impl Modules {
+    /// Sets the file of the main program, imports are resolved relative to it
+    pub fn set_script_path(&mut self, path: &Path) {
+        self.loading = vec![path.canonicalize().unwrap_or_else(|_| path.to_owned())];
+    }
+
+    /// Returns the path of the first file that exists for the import.
+    ///
+    /// Relative paths are searched next to the current file (or in the working directory) and then in the directories of `ALC_PATH`
+    pub fn resolve(&self, path: &Path) -> Option<PathBuf> {
+        let search_path = std::env::var_os(SEARCH_PATH_VAR)
+            .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        self.resolve_in(path, search_path)
+    }
+
+    /// Same as `resolve` with the directories of the search path given instead of read from `ALC_PATH`
+    pub fn resolve_in(
+        &self,
+        path: &Path,
+        search_path: impl IntoIterator<Item = PathBuf>,
+    ) -> Option<PathBuf> {
+        if path.is_absolute() {
+            return path.is_file().then(|| path.canonicalize().ok()).flatten();
+        }
+
+        let current_dir = self
+            .loading
+            .last()
+            .and_then(|file| file.parent())
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        std::iter::once(current_dir)
+            .chain(search_path)
+            .map(|dir| dir.join(path))
+            .find(|candidate| candidate.is_file())
+            .and_then(|candidate| candidate.canonicalize().ok())
+    }
+}
+
+/// Returns the path of an import, words get the module extension added
+pub fn import_path(name: &str, is_word: bool) -> PathBuf {
+    let mut path = PathBuf::from(name);
+
+    if is_word {
+        path.set_extension(MODULE_EXTENSION);
+    }
+
+    path
+}
+
+/// Returns the names of a list like `[a b]`, None if an item is not a word
+pub(crate) fn word_names(nodes: &[Node]) -> Option<Vec<Arc<str>>> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Node::Word(name) => Some(name.value.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Name of the namespace of the definitions when the import does not have `:as`
+pub fn default_prefix(path: &Path) -> Arc<str> {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into())
+        .unwrap_or_else(|| "module".into())
+}
+
+impl Program {
+    /// Evaluates `(import path :as prefix :only [names])`, defining the definitions of the module in the namespace `prefix`.
+    ///
+    /// Both options can be left out, by default every exported definition is imported in a namespace named like the file
+    // Kept out of `call_expression` so it does not grow the stack frame of every call
+    #[inline(never)]
+    pub(crate) fn import(&mut self, nodes: &[Node]) -> Result<Reference> {
+        let path = match nodes.get(1) {
+            Some(Node::StringLiteral(token)) => {
+                let len = token.value.len();
+
+                import_path(&token.value[1..(len - 1)], false)
+            }
+            Some(Node::Word(token)) => import_path(&token.value, true),
+            _ => {
+                return Ok(Reference::new(Object::Error(
+                    "Invalid path for import: should be a string or a word".into(),
+                )))
+            }
+        };
+
+        let mut prefix = default_prefix(&path);
+        let mut only = None;
+
+        for option in nodes[2..].chunks(2) {
+            match option {
+                [Node::Word(keyword), Node::Word(name)] if keyword.value.as_ref() == AS_OPTION => {
+                    prefix = name.value.clone();
+                }
+                [Node::Word(keyword), Node::List(names)]
+                    if keyword.value.as_ref() == ONLY_OPTION =>
+                {
+                    let Some(names) = word_names(names) else {
+                        return Ok(Reference::new(Object::Error(
+                            "Invalid names for import :only: should be a list of words".into(),
+                        )));
+                    };
+
+                    only = Some(names);
+                }
+                _ => return Ok(Reference::new(Object::Error(
+                    "Invalid arguments to import: expected (import path :as prefix :only [names])"
+                        .into(),
+                ))),
+            }
+        }
+
+        let module = match self.find_module(&path)? {
+            Ok(module) => module,
+            Err(err) => {
+                return Ok(Reference::new(Object::Error(
+                    format!("Cannot import {}: {}", path.display(), err).into(),
+                )))
+            }
+        };
+
+        let definitions = match only {
+            None => module.exports.clone(),
+            Some(names) => {
+                let mut definitions = Vec::with_capacity(names.len());
+
+                for name in names {
+                    let Some(value) = module.get(&name) else {
+                        let reason = if module.is_private(&name) {
+                            "it is private"
+                        } else {
+                            "it is not defined"
+                        };
+
+                        return Ok(Reference::new(Object::Error(
+                            format!("Cannot import {} from {}: {}", name, path.display(), reason)
+                                .into(),
+                        )));
+                    };
+
+                    definitions.push((name, value.clone()));
+                }
+
+                definitions
+            }
+        };
+
+        // importing again with the same prefix adds to the namespace
+        let namespace = match self.get_value(&prefix).as_ref() {
+            Object::Namespace(existing) => existing.copy(),
+            _ => Namespace::new(prefix.clone()),
+        };
+
+        for (name, value) in definitions {
+            namespace.insert(name, value);
+        }
+
+        for name in module.private.iter() {
+            namespace.mark_private(name.clone());
+        }
+
+        self.visibility.imported.insert(prefix.clone());
+        self.set_value(prefix, Reference::new(Object::Namespace(namespace)));
+
+        Ok(NULL.clone())
+    }
+
+    /// Evaluates `(export names...)`, only the exported names of a file are visible to importers
+    #[inline(never)]
+    pub(crate) fn export(&mut self, nodes: &[Node]) -> Result<Reference> {
+        let mut names = BTreeSet::new();
+
+        for node in &nodes[1..] {
+            let Node::Word(name) = node else {
+                return Ok(Reference::new(Object::Error(
+                    format!(
+                        "Invalid name for export: expected a word got {}",
+                        node.type_of()
+                    )
+                    .into(),
+                )));
+            };
+
+            names.insert(name.value.clone());
+        }
+
+        self.visibility
+            .exported
+            .get_or_insert_default()
+            .extend(names);
+
+        Ok(NULL.clone())
+    }
+
+    /// Hides the names defined by `def-` or `defn-` from importers
+    #[inline(never)]
+    pub(crate) fn mark_private(&mut self, target: &Node) {
+        let names = match target {
+            Node::Word(name) => vec![name.value.clone()],
+            pattern => Pattern::from_node(pattern)
+                .map(|pattern| pattern.names())
+                .unwrap_or_default(),
+        };
+
+        self.visibility.private.extend(names);
+    }
+
+    /// Resolves the path of an import and returns its module.
+    ///
+    /// The inner error describes why the module cannot be imported
+    pub(crate) fn find_module(&mut self, path: &Path) -> Result<Result<Arc<Module>, String>> {
+        let Some(resolved) = self.modules.lock().resolve(path) else {
+            return Ok(Err("module not found".into()));
+        };
+
+        self.load_module(&resolved)
+    }
+
+    /// Returns the definitions of a module, evaluating it if it was not imported before.
+    ///
+    /// The inner error describes an import cycle
+    pub(crate) fn load_module(&mut self, path: &Path) -> Result<Result<Arc<Module>, String>> {
+        {
+            let mut modules = self.modules.lock();
+
+            if let Some(exports) = modules.cache.get(path) {
+                return Ok(Ok(exports.clone()));
+            }
+
+            if let Some(start) = modules.loading.iter().position(|file| file == path) {
+                let cycle = modules.loading[start..]
+                    .iter()
+                    .chain([&path.to_path_buf()])
+                    .map(|file| file.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+
+                return Ok(Err(format!("import cycle: {}", cycle)));
+            }
+
+            modules.loading.push(path.to_owned());
+        }
+
+        let result = self
+            .evaluate_module(path)
+            .with_context(|| format!("in module {}", path.display()));
+
+        let mut modules = self.modules.lock();
+        modules.loading.pop();
+
+        let exports = result?;
+        modules.cache.insert(path.to_owned(), exports.clone());
+
+        Ok(Ok(exports))
+    }
+
+    /// Runs the file in a new program with only the builtins and returns what it defined
+    // Programs are big, inlining this would grow the stack frame of every call expression
+    #[inline(never)]
+    fn evaluate_module(&mut self, path: &Path) -> Result<Arc<Module>> {
+        let code = std::fs::read_to_string(path).context("to read module:")?;
+
+        self.evaluate_code(code)
+    }
+
+    /// Runs the code in a new program with only the builtins and returns what it defined
+    pub(crate) fn evaluate_code(&mut self, code: String) -> Result<Arc<Module>> {
+        let mut lexer = Lexer::from_string(code);
+        lexer.parse().context("lexer::parse")?;
+
+        let tokens = lexer.tokens();
+        if tokens.is_empty() {
+            return Ok(Arc::default());
+        }
+
+        let mut ast = AST::with_tokens(tokens);
+        let root = ast.parse().context("ast::parse")?;
+
+        if ast.has_errors() {
+            ast.print_errors(&root);
+            return Err(anyhow!("the module has syntax errors"));
+        }
+
+        let mut module = Program {
+            env: CallStack::new(EnvReference::new(EnvReferenceInner::new(
+                (*self.base_env).clone(),
+            ))),
+            sandboxed: self.sandboxed,
+            rng: self.rng.clone(),
+            modules: self.modules.clone(),
+            base_env: self.base_env.clone(),
+            visibility: Visibility::default(),
+            namespace: None,
+        };
+
+        let result = module.eval(&root)?;
+        if is_error(&result) {
+            return Err(anyhow!("{}", result));
+        }
+
+        let globals = module.env.active_slice()[0].read();
+
+        if let Some(missing) = module
+            .visibility
+            .exported
+            .iter()
+            .flatten()
+            .find(|name| !globals.contains_key(*name))
+        {
+            return Err(anyhow!("Cannot export {}: it is not defined", missing));
+        }
+
+        let definitions = globals
+            .iter()
+            .filter(|(name, value)| {
+                self.base_env
+                    .get(*name)
+                    .is_none_or(|builtin| !Arc::ptr_eq(builtin, value))
+            })
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Env>();
+
+        let module_env = EnvReference::new(EnvReferenceInner::new(definitions.clone()));
+
+        let mut exports = Module::default();
+
+        for (name, value) in definitions {
+            if !module.visibility.is_public(&name) {
+                exports.private.push(name);
+                continue;
+            }
+
+            let value = with_env(&value, &module_env).unwrap_or(value);
+
+            exports.exports.push((name, value));
+        }
+
+        exports
+            .exports
+            .sort_by(|(left, _), (right, _)| left.cmp(right));
+        exports.private.sort();
+
+        Ok(Arc::new(exports))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::{
+        interpreter::objects::Object,
+        test::{new_test_program, prepare_code},
+    };
+
+    use super::Modules;
+
+    const MODULES_DIR: &str = "examples/modules";
+
+    #[test]
+    fn import_cycles_list_the_files_of_the_cycle() {
+        let cycle_a = Path::new(MODULES_DIR).join("cycle_a.alc");
+        let code = std::fs::read_to_string(&cycle_a).unwrap();
+
+        let mut program = new_test_program();
+        program.set_script_path(&cycle_a);
+
+        let err = match program.eval(&prepare_code(code).unwrap()) {
+            Ok(result) => match result.as_ref() {
+                Object::Error(err) => err.to_string(),
+                _ => panic!("expected an error got {}", result),
+            },
+            Err(err) => format!("{:?}", err),
+        };
+
+        let cycle_a = cycle_a.canonicalize().unwrap();
+        let cycle_b = Path::new(MODULES_DIR)
+            .join("cycle_b.alc")
+            .canonicalize()
+            .unwrap();
+        let expected = format!(
+            "import cycle: {} -> {} -> {}",
+            cycle_a.display(),
+            cycle_b.display(),
+            cycle_a.display()
+        );
+
+        assert!(err.contains(&expected), "{}", err);
+    }
+
+    #[test]
+    fn private_names_cannot_be_accessed_with_the_prefix() {
+        let mut program = new_test_program();
+        program.set_script_path(Path::new(MODULES_DIR).join("main.alc"));
+
+        let code = prepare_code("(import stats)\nstats/count-of".to_owned()).unwrap();
+        let err = program.eval(&code).unwrap_err();
+
+        assert!(
+            format!("{:?}", err).contains("Cannot access count-of from stats: it is private"),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn modules_are_found_in_the_search_path() {
+        let modules = Modules::default();
+        let path = Path::new("geometry.alc");
+
+        assert_eq!(modules.resolve_in(path, []), None);
+        assert_eq!(
+            modules.resolve_in(path, [PathBuf::from("missing"), PathBuf::from(MODULES_DIR)]),
+            Some(
+                Path::new(MODULES_DIR)
+                    .join("geometry.alc")
+                    .canonicalize()
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn absolute_paths_have_to_be_files() {
+        let modules = Modules::default();
+        let dir = Path::new(MODULES_DIR).canonicalize().unwrap();
+        let file = dir.join("geometry.alc");
+
+        assert_eq!(modules.resolve_in(&dir, []), None);
+        assert_eq!(modules.resolve_in(&file, []), Some(file));
+    }
+}
@@ -0,0 +1,161 @@
+//! Forms that select and refer namespaces
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::ast::Node;
+
+use super::{
+    modules::{word_names, ONLY_OPTION},
+    objects::{Namespace, Object},
+    Program, Reference, NULL,
+};
+
+/// Namespace of the definitions that are not in any namespace, `(in-ns user)` goes back to it
+pub const USER_NAMESPACE: &str = "user";
+
+impl Program {
+    /// Returns the namespace selected with `in-ns`, None for the user namespace
+    pub fn namespace(&self) -> Option<&Namespace> {
+        self.namespace.as_ref()
+    }
+
+    /// Evaluates `(in-ns name)`, the top level definitions after it go into the namespace.
+    ///
+    /// The namespace is created if it does not exist, the namespaces of the builtins cannot be changed
+    // Kept out of `call_expression` so it does not grow the stack frame of every call
+    #[inline(never)]
+    pub(crate) fn in_namespace(&mut self, nodes: &[Node]) -> Result<Reference> {
+        let name: Arc<str> = match nodes {
+            [_, Node::Word(name)] => name.value.clone(),
+            [_, Node::StringLiteral(name)] => {
+                let len = name.value.len();
+
+                name.value[1..(len - 1)].into()
+            }
+            _ => {
+                return Ok(Reference::new(Object::Error(
+                    "Invalid arguments to in-ns: expected (in-ns name)".into(),
+                )))
+            }
+        };
+
+        if name.as_ref() == USER_NAMESPACE {
+            self.namespace = None;
+            return Ok(NULL.clone());
+        }
+
+        let globals = self.env.active_slice()[0].clone();
+        let existing = globals.read().get(&name).cloned();
+
+        let namespace = match existing {
+            None => {
+                let namespace = Namespace::new(name.clone());
+                globals.write().insert(
+                    name,
+                    Reference::new(Object::Namespace(namespace.clone())),
+                );
+
+                namespace
+            }
+            Some(value) => match value.as_ref() {
+                Object::Namespace(_)
+                    if self
+                        .base_env
+                        .get(&name)
+                        .is_some_and(|builtin| Arc::ptr_eq(builtin, &value)) =>
+                {
+                    return Ok(Reference::new(Object::Error(
+                        format!(
+                            "Cannot switch to namespace {}: the namespaces of the builtins cannot be changed",
+                            name
+                        )
+                        .into(),
+                    )))
+                }
+                Object::Namespace(namespace) => namespace.clone(),
+                object => {
+                    return Ok(Reference::new(Object::Error(
+                        format!(
+                            "Cannot switch to namespace {}: it is already defined as a {}",
+                            name,
+                            object.type_of()
+                        )
+                        .into(),
+                    )))
+                }
+            },
+        };
+
+        self.namespace = Some(namespace);
+
+        Ok(NULL.clone())
+    }
+
+    /// Evaluates `(refer namespace)` or `(refer namespace :only [names])`, defining the definitions of the namespace without its prefix
+    #[inline(never)]
+    pub(crate) fn refer(&mut self, nodes: &[Node]) -> Result<Reference> {
+        let (name, only) = match nodes {
+            [_, Node::Word(name)] => (name, None),
+            [_, Node::Word(name), Node::Word(option), Node::List(names)]
+                if option.value.as_ref() == ONLY_OPTION =>
+            {
+                let Some(names) = word_names(names) else {
+                    return Ok(Reference::new(Object::Error(
+                        "Invalid names for refer :only: should be a list of words".into(),
+                    )));
+                };
+
+                (name, Some(names))
+            }
+            _ => {
+                return Ok(Reference::new(Object::Error(
+                    "Invalid arguments to refer: expected (refer namespace) or (refer namespace :only [names])"
+                        .into(),
+                )))
+            }
+        };
+
+        let value = self.get_value(&name.value);
+        let Object::Namespace(namespace) = value.as_ref() else {
+            return Ok(Reference::new(Object::Error(
+                format!(
+                    "Cannot refer {}: expected a namespace got {}",
+                    name.value,
+                    value.type_of()
+                )
+                .into(),
+            )));
+        };
+
+        let bindings = match only {
+            None => namespace.bindings(),
+            Some(names) => {
+                let mut bindings = Vec::with_capacity(names.len());
+
+                for name in names {
+                    let Some(value) = namespace.get(&name) else {
+                        return Ok(Reference::new(Object::Error(
+                            format!(
+                                "Cannot refer {} from {}: it is not defined",
+                                name,
+                                namespace.name()
+                            )
+                            .into(),
+                        )));
+                    };
+
+                    bindings.push((name, value));
+                }
+
+                bindings
+            }
+        };
+
+        for (name, value) in bindings {
+            self.set_value(name, value);
+        }
+
+        Ok(NULL.clone())
+    }
+}
@@ -0,0 +1,58 @@
+//! Mutable cell shared by every copy of the value
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::interpreter::Reference;
+
+/// Holds a value that can be replaced with `reset!` and `swap!`.
+///
+/// An atom cannot hold itself, but one that holds itself through a list, a map or another atom is never freed
+#[derive(Clone)]
+pub struct Atom(Arc<RwLock<Reference>>);
+
+impl Atom {
+    pub fn new(value: Reference) -> Self {
+        Self(Arc::new(RwLock::new(value)))
+    }
+
+    /// Returns the current value
+    pub fn get(&self) -> Reference {
+        self.0.read().clone()
+    }
+
+    /// Replaces the value and returns it
+    pub fn set(&self, value: Reference) -> Reference {
+        *self.0.write() = value.clone();
+
+        value
+    }
+
+    /// Replaces the value only if it is still `expected`, returns false when it was changed in between
+    pub fn compare_and_set(&self, expected: &Reference, value: Reference) -> bool {
+        let mut current = self.0.write();
+
+        if !Arc::ptr_eq(&current, expected) {
+            return false;
+        }
+
+        *current = value;
+
+        true
+    }
+
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    pub fn as_ptr(&self) -> *const () {
+        Arc::as_ptr(&self.0).cast()
+    }
+}
+
+// Only the address is printed since an atom can contain itself
+impl std::fmt::Debug for Atom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Atom({:p})", self.as_ptr())
+    }
+}
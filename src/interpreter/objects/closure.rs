@@ -0,0 +1,120 @@
+//! Functions built at runtime from other values, like the result of `partial` or `memoize`
+use std::collections::BTreeMap;
+
+use parking_lot::Mutex;
+
+use crate::interpreter::{
+    builtins::errors::new_args_len_error, is_error, List, Program, Reference, NULL,
+};
+
+use super::Object;
+
+/// Callable value that wraps other values
+#[derive(Debug)]
+pub enum Closure {
+    /// Calls the function with `args` followed by the arguments of the call
+    Partial { function: Reference, args: List },
+    /// Calls the last function with the arguments and each previous function with the result of the next one
+    Compose(List),
+    /// Ignores the arguments and returns the value
+    Constantly(Reference),
+    /// Calls every function with the arguments and returns a list with the results
+    Juxt(List),
+    /// Calls the function only once for each list of arguments
+    Memoize {
+        function: Reference,
+        cache: Mutex<BTreeMap<Vec<Reference>, Reference>>,
+    },
+}
+
+impl Closure {
+    pub fn call(&self, program: &mut Program, args: Vec<Reference>) -> anyhow::Result<Reference> {
+        match self {
+            Closure::Partial {
+                function,
+                args: partial_args,
+            } => {
+                let args = partial_args.iter().cloned().chain(args).collect();
+
+                program.call(function, args)
+            }
+            Closure::Compose(functions) => {
+                let mut functions = functions.iter().rev();
+
+                // without functions it is the identity
+                let Some(first) = functions.next() else {
+                    if args.len() > 1 {
+                        return Ok(new_args_len_error("comp", &args, "0 to 1"));
+                    }
+
+                    return Ok(args.into_iter().next().unwrap_or_else(|| NULL.clone()));
+                };
+
+                let mut result = program.call(first, args)?;
+
+                for function in functions {
+                    if is_error(&result) {
+                        break;
+                    }
+
+                    result = program.call(function, vec![result])?;
+                }
+
+                Ok(result)
+            }
+            Closure::Constantly(value) => Ok(value.clone()),
+            Closure::Juxt(functions) => {
+                let mut results = List::new();
+
+                for function in functions {
+                    let result = program.call(function, args.clone())?;
+
+                    if is_error(&result) {
+                        return Ok(result);
+                    }
+
+                    results.push_back(result);
+                }
+
+                Ok(Reference::new(Object::List(results)))
+            }
+            Closure::Memoize { function, cache } => {
+                if let Some(result) = cache.lock().get(&args) {
+                    return Ok(result.clone());
+                }
+
+                // the lock is not held during the call so recursive calls can use the cache
+                let result = program.call(function, args.clone())?;
+
+                if !is_error(&result) {
+                    cache.lock().insert(args, result.clone());
+                }
+
+                Ok(result)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::objects::Object,
+        test::{new_test_program, prepare_code},
+    };
+
+    #[test]
+    fn empty_comp_takes_a_single_argument() {
+        let mut program = new_test_program();
+
+        let err = match program.eval(&prepare_code("((comp) 1 2)".to_owned()).unwrap()) {
+            Ok(result) => match result.as_ref() {
+                Object::Error(err) => err.to_string(),
+                _ => panic!("expected an error got {}", result),
+            },
+            Err(err) => format!("{:?}", err),
+        };
+
+        assert!(err.contains("expected: 0 to 1 got: 2"), "{}", err);
+    }
+}
@@ -0,0 +1,54 @@
+//! Clauses of user defined functions
+use crate::{
+    ast::{Node, Parameters},
+    interpreter::{EnvReference, Reference},
+};
+
+use super::Object;
+
+/// Parameters and body used when a function is called with an amount of arguments they accept
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS), ts(export))]
+pub struct Arity {
+    pub parameters: Parameters,
+    pub body: Node,
+}
+
+/// Returns the first arity that accepts the amount of arguments
+pub fn select_arity(arities: &[Arity], len: usize) -> Option<&Arity> {
+    arities.iter().find(|arity| arity.parameters.accepts(len))
+}
+
+/// Joins the description of every arity, like `1 or 2`
+pub fn describe_arities(
+    arities: &[Arity],
+    separator: &str,
+    describe: impl Fn(&Parameters) -> String,
+) -> String {
+    arities
+        .iter()
+        .map(|arity| describe(&arity.parameters))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Returns a copy of a user defined function that finds the names of the environment when it is called.
+///
+/// Functions look up names when they are called, modules and namespaces use it so their functions find
+/// the other definitions when they are called from somewhere else
+pub fn with_env(value: &Reference, env: &EnvReference) -> Option<Reference> {
+    let Object::Function {
+        name, doc, arities, ..
+    } = value.as_ref()
+    else {
+        return None;
+    };
+
+    Some(Reference::new(Object::Function {
+        env: env.clone(),
+        name: name.clone(),
+        doc: doc.clone(),
+        arities: arities.clone(),
+    }))
+}
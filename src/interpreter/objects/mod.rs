@@ -1,17 +1,30 @@
-use std::{fmt::Display, sync::Arc};
+use std::{any::Any, fmt::Display, sync::Arc};
 
-use crate::ast::Node;
+use parking_lot::Mutex;
 
-use super::{EnvReference, Program, Reference};
+use crate::ast::Parameters;
+
+use super::{builtins::compare_integer_float, EnvReference, List, Map, Program, Reference};
 
 #[cfg(feature = "serde")]
 use super::NULL;
 #[cfg(feature = "serde")]
 use std::sync::LazyLock;
 
+mod atom;
+mod closure;
+mod function;
+mod namespace;
+mod sequence;
 #[cfg(feature = "wasm")]
 mod wasm;
 
+pub use atom::Atom;
+pub use closure::Closure;
+pub use function::{describe_arities, select_arity, with_env, Arity};
+pub use namespace::{split_qualified, Namespace, NAMESPACE_SEPARATOR};
+pub use sequence::{NativeSource, Sequence, Source};
+
 #[derive(Debug, Clone)]
 #[cfg_attr(
     feature = "serde",
@@ -22,9 +35,11 @@ mod wasm;
 pub enum Object {
     Null,
     Integer(isize),
+    Float(f64),
     String(Arc<str>),
     Bool(bool),
-    List(Arc<[Reference]>),
+    List(#[cfg_attr(feature = "ts-rs", ts(as = "Vec<Reference>"))] List),
+    Map(Arc<Map>),
     Builtin {
         #[cfg_attr(feature = "serde", serde(default = "get_default_builtin", skip))]
         function: BuiltinFunction,
@@ -32,10 +47,44 @@ pub enum Object {
     Function {
         #[cfg_attr(feature = "ts-rs", ts(skip))]
         env: EnvReference,
-        parameters: Arc<[Arc<str>]>,
-        body: Node,
+        /// Name the function was defined with, None for anonymous functions
+        name: Option<Arc<str>>,
+        doc: Option<Arc<str>>,
+        arities: Arc<[Arity]>,
     },
     Error(Arc<str>),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    Resource(Resource),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    Sequence(Sequence),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    Closure(Arc<Closure>),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    Atom(Atom),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    Namespace(Namespace),
+}
+
+/// Opaque handle to a value owned by the host, like an open file.
+#[derive(Debug, Clone)]
+pub struct Resource {
+    /// Name of the resource, used as its type
+    pub kind: &'static str,
+    pub value: Arc<Mutex<dyn Any + Send>>,
+}
+
+impl Resource {
+    pub fn new<T: Any + Send>(kind: &'static str, value: T) -> Self {
+        Self {
+            kind,
+            value: Arc::new(Mutex::new(value)),
+        }
+    }
 }
 
 pub type BuiltinFunction = fn(&mut Program, Vec<Reference>) -> Reference;
@@ -54,14 +103,29 @@ impl Object {
         match self {
             Object::Null => "null",
             Object::Integer(_) => "number",
+            Object::Float(_) => "float",
             Object::String(_) => "string",
             Object::Bool(_) => "bool",
             Object::List(_) => "list",
+            Object::Map(_) => "map",
             Object::Builtin { .. } => "builtin",
             Object::Function { .. } => "function",
             Object::Error(_) => "error",
+            Object::Resource(resource) => resource.kind,
+            Object::Sequence(_) => "sequence",
+            Object::Closure(_) => "function",
+            Object::Atom(_) => "atom",
+            Object::Namespace(_) => "namespace",
         }
     }
+
+    /// Returns true if the value can be called like a function
+    pub fn is_callable(&self) -> bool {
+        matches!(
+            self,
+            Object::Builtin { .. } | Object::Function { .. } | Object::Closure(_)
+        )
+    }
 }
 
 impl Display for Object {
@@ -69,6 +133,7 @@ impl Display for Object {
         match self {
             Object::Null => f.write_str("null"),
             Object::Integer(v) => write!(f, "{}", v),
+            Object::Float(v) => write!(f, "{:?}", v),
             Object::String(v) => f.write_fmt(format_args!("\"{}\"", v)),
             Object::Bool(v) => write!(f, "{}", v),
             Object::List(vec) => {
@@ -83,15 +148,45 @@ impl Display for Object {
                 f.write_str("]")?;
                 Ok(())
             }
+            Object::Map(map) => {
+                f.write_str("{")?;
+                let len = map.len();
+                for (i, (k, v)) in map.iter().enumerate() {
+                    write!(f, "\"{}\" ", k)?;
+                    v.fmt(f)?;
+                    if i != (len - 1) {
+                        f.write_str(" ")?;
+                    }
+                }
+                f.write_str("}")?;
+                Ok(())
+            }
             Object::Builtin { function } => {
                 write!(f, "BUILTIN[{:?}]", function)
             }
-            Object::Function { .. } => {
-                write!(f, "FUNCTION[{:p}]", self)
+            Object::Function { name, arities, .. } => {
+                write!(
+                    f,
+                    "FUNCTION[{}/{}]",
+                    name.as_deref().unwrap_or("fn"),
+                    describe_arities(arities, ",", Parameters::short_arity)
+                )
             }
             Object::Error(msg) => {
                 write!(f, "ERROR[{}]", msg)
             }
+            Object::Resource(resource) => {
+                write!(
+                    f,
+                    "{}[{:p}]",
+                    resource.kind.to_uppercase(),
+                    Arc::as_ptr(&resource.value) as *const ()
+                )
+            }
+            Object::Sequence(sequence) => write!(f, "SEQUENCE[{:p}]", sequence.as_ptr()),
+            Object::Closure(closure) => write!(f, "FUNCTION[{:p}]", Arc::as_ptr(closure)),
+            Object::Atom(atom) => write!(f, "ATOM[{:p}]", atom.as_ptr()),
+            Object::Namespace(namespace) => write!(f, "NAMESPACE[{}]", namespace.name()),
         }
     }
 }
@@ -100,9 +195,11 @@ impl PartialEq for Object {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Integer(l0), Self::Integer(r0)) => l0 == r0,
+            (Self::Float(l0), Self::Float(r0)) => l0.total_cmp(r0).is_eq(),
             (Self::String(l0), Self::String(r0)) => l0 == r0,
             (Self::Bool(l0), Self::Bool(r0)) => l0 == r0,
             (Self::List(l0), Self::List(r0)) => l0 == r0,
+            (Self::Map(l0), Self::Map(r0)) => l0 == r0,
             (
                 Self::Builtin {
                     function: l_function,
@@ -114,20 +211,21 @@ impl PartialEq for Object {
             (
                 Self::Function {
                     env: l_env,
-                    parameters: l_parameters,
-                    body: l_body,
+                    arities: l_arities,
+                    ..
                 },
                 Self::Function {
                     env: r_env,
-                    parameters: r_parameters,
-                    body: r_body,
+                    arities: r_arities,
+                    ..
                 },
-            ) => {
-                std::ptr::addr_eq(l_env, r_env)
-                    && std::ptr::addr_eq(l_parameters, r_parameters)
-                    && std::ptr::addr_eq(l_body, r_body)
-            }
+            ) => std::ptr::addr_eq(l_env, r_env) && Arc::ptr_eq(l_arities, r_arities),
             (Self::Error(l0), Self::Error(r0)) => l0 == r0,
+            (Self::Resource(l0), Self::Resource(r0)) => Arc::ptr_eq(&l0.value, &r0.value),
+            (Self::Sequence(l0), Self::Sequence(r0)) => l0.ptr_eq(r0),
+            (Self::Closure(l0), Self::Closure(r0)) => Arc::ptr_eq(l0, r0),
+            (Self::Atom(l0), Self::Atom(r0)) => l0.ptr_eq(r0),
+            (Self::Namespace(l0), Self::Namespace(r0)) => l0.ptr_eq(r0),
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
@@ -140,26 +238,94 @@ impl PartialOrd for Object {
     }
 }
 
+impl Object {
+    /// Position of the type in the ordering between values of different types
+    fn type_rank(&self) -> u8 {
+        match self {
+            Object::Null => 0,
+            Object::Bool(_) => 1,
+            Object::Integer(_) | Object::Float(_) => 2,
+            Object::String(_) => 3,
+            Object::List(_) => 4,
+            Object::Map(_) => 5,
+            Object::Builtin { .. } => 6,
+            Object::Function { .. } => 7,
+            Object::Error(_) => 8,
+            Object::Resource(_) => 9,
+            Object::Sequence(_) => 10,
+            Object::Closure(_) => 11,
+            Object::Atom(_) => 12,
+            Object::Namespace(_) => 13,
+        }
+    }
+}
+
+/// Orders an integer and a float by value, placing the integer first when they are equal.
+///
+/// NaN goes where `total_cmp` puts it, after every integer or before them if it's negative
+fn integer_float_order(integer: isize, float: f64) -> std::cmp::Ordering {
+    match compare_integer_float(integer, float) {
+        Some(ordering) => ordering.then(std::cmp::Ordering::Less),
+        None if float.is_sign_negative() => std::cmp::Ordering::Greater,
+        None => std::cmp::Ordering::Less,
+    }
+}
+
+/// Total ordering between values.
+///
+/// Values of different types are ordered by type, integers and floats are compared by value,
+/// lists and maps are compared element by element and functions by identity
 impl Ord for Object {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         match (self, other) {
-            (Object::Null, _) => std::cmp::Ordering::Less,
+            (Object::Null, Object::Null) => std::cmp::Ordering::Equal,
             (Object::Bool(left), Object::Bool(right)) => left.cmp(right),
-            (Object::Bool(_), _) => std::cmp::Ordering::Less,
             (Object::Integer(left), Object::Integer(right)) => left.cmp(right),
-            (Object::Integer(_), _) => std::cmp::Ordering::Less,
+            (Object::Float(left), Object::Float(right)) => left.total_cmp(right),
+            // integers go first when a float has the same value
+            (Object::Integer(left), Object::Float(right)) => integer_float_order(*left, *right),
+            (Object::Float(left), Object::Integer(right)) => {
+                integer_float_order(*right, *left).reverse()
+            }
             (Object::String(left), Object::String(right)) => left.cmp(right),
-            (Object::String(_), _) => std::cmp::Ordering::Less,
-            (Object::List(left), Object::List(right)) => {
-                let llen = left.len();
-                let rlen = right.len();
-
-                llen.cmp(&rlen)
+            (Object::List(left), Object::List(right)) => left.iter().cmp(right.iter()),
+            (Object::Map(left), Object::Map(right)) => left.cmp(right),
+            (Object::Builtin { function: left }, Object::Builtin { function: right }) => {
+                (*left as usize).cmp(&(*right as usize))
+            }
+            // the same pointers that `eq` compares, so equal functions are ordered as equal
+            (
+                Object::Function {
+                    env: left_env,
+                    arities: left_arities,
+                    ..
+                },
+                Object::Function {
+                    env: right_env,
+                    arities: right_arities,
+                    ..
+                },
+            ) => std::ptr::from_ref(left_env)
+                .cast::<()>()
+                .cmp(&std::ptr::from_ref(right_env).cast::<()>())
+                .then_with(|| {
+                    Arc::as_ptr(left_arities)
+                        .cast::<()>()
+                        .cmp(&Arc::as_ptr(right_arities).cast::<()>())
+                }),
+            (Object::Error(left), Object::Error(right)) => left.cmp(right),
+            (Object::Resource(left), Object::Resource(right)) => Arc::as_ptr(&left.value)
+                .cast::<()>()
+                .cmp(&Arc::as_ptr(&right.value).cast::<()>()),
+            (Object::Sequence(left), Object::Sequence(right)) => left.as_ptr().cmp(&right.as_ptr()),
+            (Object::Closure(left), Object::Closure(right)) => {
+                Arc::as_ptr(left).cmp(&Arc::as_ptr(right))
+            }
+            (Object::Atom(left), Object::Atom(right)) => left.as_ptr().cmp(&right.as_ptr()),
+            (Object::Namespace(left), Object::Namespace(right)) => {
+                left.as_ptr().cmp(&right.as_ptr())
             }
-            (Object::List(_), _) => std::cmp::Ordering::Less,
-            (Object::Builtin { .. }, _) => std::cmp::Ordering::Less,
-            (Object::Function { .. }, _) => std::cmp::Ordering::Less,
-            (Object::Error { .. }, _) => std::cmp::Ordering::Less,
+            _ => self.type_rank().cmp(&other.type_rank()),
         }
     }
 }
@@ -0,0 +1,131 @@
+//! Named groups of definitions, looked up with `namespace/name`
+use std::{collections::BTreeSet, sync::Arc};
+
+use parking_lot::RwLock;
+
+use crate::interpreter::{Env, EnvReference, EnvReferenceInner, Reference};
+
+use super::{with_env, Object};
+
+/// Separator between the name of a namespace and the name of a definition
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+/// Definitions grouped under a name, shared by every copy of the value
+#[derive(Clone)]
+pub struct Namespace {
+    name: Arc<str>,
+    bindings: EnvReference,
+    /// Names of an imported module that it keeps private, looking them up is an error
+    private: Arc<RwLock<BTreeSet<Arc<str>>>>,
+}
+
+impl Namespace {
+    pub fn new(name: impl Into<Arc<str>>) -> Self {
+        Self::with_bindings(name, Env::default())
+    }
+
+    pub fn with_bindings(name: impl Into<Arc<str>>, bindings: Env) -> Self {
+        Self {
+            name: name.into(),
+            bindings: EnvReference::new(EnvReferenceInner::new(bindings)),
+            private: Arc::default(),
+        }
+    }
+
+    pub fn name(&self) -> &Arc<str> {
+        &self.name
+    }
+
+    /// Returns a definition, functions defined in the namespace get its environment to find the other
+    /// definitions when they are called from outside of it
+    pub fn get(&self, name: &str) -> Option<Reference> {
+        self.binding(name).map(|value| self.enclose(value))
+    }
+
+    /// Returns a definition as it was added
+    pub(crate) fn binding(&self, name: &str) -> Option<Reference> {
+        self.bindings.read().get(name).cloned()
+    }
+
+    // Functions are not stored with the environment of the namespace, that would be a reference cycle
+    fn enclose(&self, value: Reference) -> Reference {
+        match value.as_ref() {
+            Object::Function { env, .. } if env.read().is_empty() => {
+                with_env(&value, &self.bindings).unwrap_or(value)
+            }
+            _ => value,
+        }
+    }
+
+    pub fn insert(&self, name: impl Into<Arc<str>>, value: Reference) {
+        self.bindings.write().insert(name.into(), value);
+    }
+
+    /// Marks a name as private, it is not defined in the namespace but looking it up explains why
+    pub fn mark_private(&self, name: impl Into<Arc<str>>) {
+        self.private.write().insert(name.into());
+    }
+
+    pub fn is_private(&self, name: &str) -> bool {
+        self.private.read().contains(name)
+    }
+
+    /// Looks up a name that can be qualified by the namespaces nested inside this one, like `inner/name`
+    pub fn resolve(&self, path: &str) -> Option<Reference> {
+        if let Some(value) = self.get(path) {
+            return Some(value);
+        }
+
+        let (inner, rest) = split_qualified(path)?;
+
+        match self.binding(inner)?.as_ref() {
+            Object::Namespace(namespace) => namespace.resolve(rest),
+            _ => None,
+        }
+    }
+
+    /// Returns the definitions sorted by name
+    pub fn bindings(&self) -> Vec<(Arc<str>, Reference)> {
+        let mut bindings = self
+            .bindings
+            .read()
+            .iter()
+            .map(|(name, value)| (name.clone(), self.enclose(value.clone())))
+            .collect::<Vec<_>>();
+
+        bindings.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        bindings
+    }
+
+    /// Returns a namespace with a copy of the definitions, changes to one are not seen by the other
+    pub fn copy(&self) -> Self {
+        let copy = Self::with_bindings(self.name.clone(), self.bindings.read().clone());
+        copy.private.write().clone_from(&self.private.read());
+
+        copy
+    }
+
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.bindings, &other.bindings)
+    }
+
+    pub fn as_ptr(&self) -> *const () {
+        Arc::as_ptr(&self.bindings).cast()
+    }
+}
+
+/// Splits `namespace/name` in its two parts, None if the name is not qualified.
+///
+/// The division function `/` and names that start or end with the separator are not qualified
+pub fn split_qualified(name: &str) -> Option<(&str, &str)> {
+    name.split_once(NAMESPACE_SEPARATOR)
+        .filter(|(namespace, rest)| !namespace.is_empty() && !rest.is_empty())
+}
+
+// Only the name is printed since a namespace can contain itself
+impl std::fmt::Debug for Namespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Namespace({})", self.name)
+    }
+}
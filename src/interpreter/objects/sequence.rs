@@ -0,0 +1,359 @@
+//! Lazy sequences. Items are computed when they are first needed and remembered after that.
+//!
+//! A sequence that is only used by the next stage of a chain (like the range of `(map (lazy_range) f)`)
+//! forgets its items once that stage reads them, since nothing else can read them again
+use std::{collections::VecDeque, fmt::Debug, sync::Arc};
+
+use parking_lot::Mutex;
+
+use crate::interpreter::{builtins::call_function, is_error, is_truthy, List, Program, Reference};
+
+use super::Object;
+
+/// Host iterator used as the source of a sequence, like the lines of a file
+pub type NativeSource = Box<dyn Iterator<Item = Reference> + Send>;
+
+/// Where the items of a sequence come from
+pub enum Source {
+    /// Numbers from `next` until `end` (exclusive), no end means the range is infinite
+    Range {
+        next: Option<isize>,
+        end: Option<isize>,
+        step: isize,
+    },
+    /// `value`, then the function called with `value`, then the function called with that result...
+    Iterate {
+        value: Reference,
+        function: Reference,
+        started: bool,
+    },
+    /// The same value forever or `remaining` times
+    Repeat {
+        value: Reference,
+        remaining: Option<usize>,
+    },
+    /// The items of a list over and over
+    Cycle {
+        items: List,
+        idx: usize,
+    },
+    Native(NativeSource),
+    Map {
+        sequence: Sequence,
+        function: Reference,
+        idx: usize,
+    },
+    Filter {
+        sequence: Sequence,
+        function: Reference,
+        idx: usize,
+    },
+    Take {
+        sequence: Sequence,
+        remaining: usize,
+        idx: usize,
+    },
+}
+
+impl Source {
+    /// Computes the next item. `None` when the source has no more items
+    fn next(&mut self, program: &mut Program) -> Result<Option<Reference>, Reference> {
+        match self {
+            Source::Range { next, end, step } => {
+                let Some(value) = *next else {
+                    return Ok(None);
+                };
+
+                let in_range = match end {
+                    Some(end) if *step > 0 => value < *end,
+                    Some(end) => value > *end,
+                    None => true,
+                };
+
+                if !in_range {
+                    return Ok(None);
+                }
+
+                *next = value.checked_add(*step);
+
+                Ok(Some(Reference::new(Object::Integer(value))))
+            }
+            Source::Iterate {
+                value,
+                function,
+                started,
+            } => {
+                if *started {
+                    *value = call_function(program, "iterate", function, vec![value.clone()])?;
+                }
+
+                *started = true;
+
+                Ok(Some(value.clone()))
+            }
+            Source::Repeat { value, remaining } => match remaining {
+                Some(0) => Ok(None),
+                Some(remaining) => {
+                    *remaining -= 1;
+                    Ok(Some(value.clone()))
+                }
+                None => Ok(Some(value.clone())),
+            },
+            Source::Cycle { items, idx } => {
+                if items.is_empty() {
+                    return Ok(None);
+                }
+
+                let item = items[*idx % items.len()].clone();
+                *idx += 1;
+
+                Ok(Some(item))
+            }
+            Source::Native(iterator) => match iterator.next() {
+                Some(item) if is_error(&item) => Err(item),
+                item => Ok(item),
+            },
+            Source::Map {
+                sequence,
+                function,
+                idx,
+            } => {
+                let Some(item) = sequence.next_from(program, idx)? else {
+                    return Ok(None);
+                };
+
+                call_function(program, "map", function, vec![item]).map(Some)
+            }
+            Source::Filter {
+                sequence,
+                function,
+                idx,
+            } => loop {
+                let Some(item) = sequence.next_from(program, idx)? else {
+                    return Ok(None);
+                };
+
+                let keep = call_function(program, "filter", function, vec![item.clone()])?;
+
+                if is_truthy(&keep) {
+                    return Ok(Some(item));
+                }
+            },
+            Source::Take {
+                sequence,
+                remaining,
+                idx,
+            } => {
+                if *remaining == 0 {
+                    return Ok(None);
+                }
+
+                let item = sequence.next_from(program, idx)?;
+
+                *remaining -= 1;
+
+                Ok(item)
+            }
+        }
+    }
+}
+
+struct State {
+    /// Items computed so far that can still be read
+    items: VecDeque<Reference>,
+    /// Amount of items that were computed and forgotten, `items` starts at this position
+    released: usize,
+    /// `None` once the source has no more items or while the next item is being computed
+    source: Option<Source>,
+    computing: bool,
+}
+
+/// Lazy sequence of values. Clones share the computed items
+#[derive(Clone)]
+pub struct Sequence(Arc<Mutex<State>>);
+
+impl Debug for Sequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Sequence({:p})", Arc::as_ptr(&self.0))
+    }
+}
+
+impl Sequence {
+    pub fn new(source: Source) -> Self {
+        Self(Arc::new(Mutex::new(State {
+            items: VecDeque::new(),
+            released: 0,
+            source: Some(source),
+            computing: false,
+        })))
+    }
+
+    pub fn ptr_eq(&self, other: &Sequence) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    pub fn as_ptr(&self) -> *const () {
+        Arc::as_ptr(&self.0) as *const ()
+    }
+
+    /// Returns the item at the position, computing the items before it if needed.
+    ///
+    /// `None` when the sequence has less items
+    pub fn get(&self, program: &mut Program, idx: usize) -> Result<Option<Reference>, Reference> {
+        loop {
+            // the lock is not held while computing because the functions can use this sequence
+            let mut source = {
+                let mut state = self.0.lock();
+
+                let Some(position) = idx.checked_sub(state.released) else {
+                    return Err(Reference::new(Object::Error(
+                        "sequence item was already released".into(),
+                    )));
+                };
+
+                if let Some(item) = state.items.get(position) {
+                    return Ok(Some(item.clone()));
+                }
+
+                match state.source.take() {
+                    Some(source) => {
+                        state.computing = true;
+                        source
+                    }
+                    None if state.computing => {
+                        return Err(Reference::new(Object::Error(
+                            "sequence depends on an item of itself that is not computed yet".into(),
+                        )));
+                    }
+                    None => return Ok(None),
+                }
+            };
+
+            let next = source.next(program);
+
+            let mut state = self.0.lock();
+            state.computing = false;
+
+            match next {
+                Ok(Some(item)) => {
+                    state.items.push_back(item);
+                    state.source = Some(source);
+                }
+                Ok(None) => return Ok(None),
+                Err(err) => {
+                    state.source = Some(source);
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    /// Returns the item at `idx` and moves it to the next item.
+    ///
+    /// When nothing else holds the sequence the items up to `idx` are forgotten, they cannot be read again
+    pub fn next_from(
+        &self,
+        program: &mut Program,
+        idx: &mut usize,
+    ) -> Result<Option<Reference>, Reference> {
+        let item = self.get(program, *idx)?;
+
+        if item.is_some() {
+            *idx += 1;
+
+            if !self.is_shared() {
+                self.release(*idx);
+            }
+        }
+
+        Ok(item)
+    }
+
+    /// Returns true if other values hold this sequence and can read its items
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+
+    /// Forgets the items before the position, only for sequences that nothing else can read
+    pub(crate) fn release(&self, idx: usize) {
+        let mut state = self.0.lock();
+
+        let count = idx.saturating_sub(state.released).min(state.items.len());
+        state.items.drain(..count);
+        state.released += count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::{objects::Object, Program, Reference},
+        test::new_test_program,
+    };
+
+    use super::{Sequence, Source};
+
+    fn natural_numbers() -> Sequence {
+        Sequence::new(Source::Range {
+            next: Some(0),
+            end: None,
+            step: 1,
+        })
+    }
+
+    fn take(sequence: Sequence, remaining: usize) -> Sequence {
+        Sequence::new(Source::Take {
+            sequence,
+            remaining,
+            idx: 0,
+        })
+    }
+
+    fn read(program: &mut Program, sequence: &Sequence, count: usize) {
+        let mut idx = 0;
+        while idx < count && sequence.next_from(program, &mut idx).unwrap().is_some() {}
+    }
+
+    #[test]
+    fn shared_sequences_remember_their_items() {
+        let mut program = new_test_program();
+
+        let numbers = natural_numbers();
+        read(&mut program, &take(numbers.clone(), 100), 100);
+
+        assert_eq!(numbers.0.lock().items.len(), 100);
+        assert_eq!(
+            numbers.get(&mut program, 99).unwrap(),
+            Some(Reference::new(Object::Integer(99)))
+        );
+    }
+
+    #[test]
+    fn stages_forget_the_items_nothing_else_can_read() {
+        let mut program = new_test_program();
+
+        let identity = Sequence::new(Source::Map {
+            sequence: natural_numbers(),
+            function: Reference::new(Object::Builtin {
+                function: |_, args| args[0].clone(),
+            }),
+            idx: 0,
+        });
+        let first = take(identity, 1000);
+        read(&mut program, &first, 100);
+
+        let Some(Source::Take { sequence: map, .. }) = first.0.lock().source.take() else {
+            panic!("take should keep its source until it ends");
+        };
+        let Some(Source::Map {
+            sequence: range, ..
+        }) = map.0.lock().source.take()
+        else {
+            panic!("map should keep its source until it ends");
+        };
+
+        assert!(first.0.lock().items.is_empty());
+        assert!(map.0.lock().items.is_empty());
+        assert!(range.0.lock().items.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use std::cell::LazyCell;
+use std::{cell::LazyCell, sync::Arc};
 
 use js_sys::JsString;
 use wasm_bindgen::JsValue;
@@ -15,6 +15,7 @@ impl From<Object> for wasm_bindgen::JsValue {
         match value {
             Object::Null => wasm_bindgen::JsValue::NULL,
             Object::Integer(value) => wasm_bindgen::JsValue::from(value as i32),
+            Object::Float(value) => wasm_bindgen::JsValue::from(value),
             Object::String(st) => JsString::from(st.as_ref()).into(),
             Object::Bool(value) => {
                 if value {
@@ -32,9 +33,38 @@ impl From<Object> for wasm_bindgen::JsValue {
 
                 array.into()
             }
+            Object::Map(map) => {
+                let object = js_sys::Object::new();
+
+                map.iter().for_each(|(key, value)| {
+                    let _ = js_sys::Reflect::set(
+                        &object,
+                        &JsString::from(key.as_ref()),
+                        &JsValue::from(value.as_ref().clone()),
+                    );
+                });
+
+                object.into()
+            }
             Object::Builtin { .. } => BUILTIN_MESSAGE.with(|m| m.as_ref().clone()),
-            Object::Function { env, .. } => JsString::from(format!("FUNCTION [{:p}]", env)).into(),
+            function @ Object::Function { .. } => JsString::from(function.to_string()).into(),
             Object::Error(_) => todo!(),
+            Object::Resource(resource) => JsString::from(format!(
+                "{} [{:p}]",
+                resource.kind,
+                Arc::as_ptr(&resource.value) as *const ()
+            ))
+            .into(),
+            Object::Closure(closure) => {
+                JsString::from(format!("FUNCTION [{:p}]", Arc::as_ptr(&closure))).into()
+            }
+            Object::Sequence(sequence) => {
+                JsString::from(format!("sequence [{:p}]", sequence.as_ptr())).into()
+            }
+            Object::Atom(atom) => JsString::from(format!("atom [{:p}]", atom.as_ptr())).into(),
+            Object::Namespace(namespace) => {
+                JsString::from(format!("namespace [{}]", namespace.name())).into()
+            }
         }
     }
 }
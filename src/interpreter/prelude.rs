@@ -0,0 +1,49 @@
+//! Standard functions written in alc-lisp
+use anyhow::{Context, Result};
+
+use super::{
+    builtins::STD_NAMESPACE,
+    objects::{with_env, Namespace, Object},
+    Env, EnvReference, EnvReferenceInner, Program, Reference,
+};
+
+/// Source of the prelude, embedded in the binary
+pub const PRELUDE: &str = include_str!("prelude.alc");
+
+/// Evaluates the prelude with the builtins of the environment and adds its definitions like the builtins,
+/// without a prefix and in the `std` namespace.
+///
+/// Prelude functions get an environment with the builtins and the other prelude definitions. Names are
+/// looked up in the environment of the function before the caller's, so caller locals and user definitions
+/// with the same names as the builtins do not change them
+pub fn add_prelude(env: &mut Env) -> Result<()> {
+    let mut program = Program::new(env.clone());
+    let prelude = program
+        .evaluate_code(PRELUDE.to_owned())
+        .context("to evaluate the prelude")?;
+
+    // the definitions in the environment keep an empty one, a function holding its own environment would be a cycle
+    let mut prelude_env = env.clone();
+    for (name, value) in prelude.exports.iter() {
+        let value = with_env(value, &EnvReference::default()).unwrap_or_else(|| value.clone());
+        prelude_env.insert(name.clone(), value);
+    }
+    let prelude_env = EnvReference::new(EnvReferenceInner::new(prelude_env));
+
+    // the namespace is copied so the environment without the prelude keeps its own
+    let std = match env.get(STD_NAMESPACE).map(|value| value.as_ref()) {
+        Some(Object::Namespace(std)) => std.copy(),
+        _ => Namespace::new(STD_NAMESPACE),
+    };
+
+    for (name, value) in prelude.exports.iter() {
+        let value = with_env(value, &prelude_env).unwrap_or_else(|| value.clone());
+
+        env.insert(name.clone(), value.clone());
+        std.insert(name.clone(), value);
+    }
+
+    env.insert(STD_NAMESPACE.into(), Reference::new(Object::Namespace(std)));
+
+    Ok(())
+}
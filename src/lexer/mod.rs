@@ -26,7 +26,7 @@ impl Lexer {
     fn is_word_symbol(c: char) -> bool {
         matches!(
             c,
-            '+' | '-' | '/' | '*' | '_' | '=' | '?' | '!' | '<' | '>' | '%'
+            '+' | '-' | '/' | '*' | '_' | '=' | '?' | '!' | '<' | '>' | '%' | '&' | ':'
         )
     }
 
@@ -36,6 +36,11 @@ impl Lexer {
         let mut col = 0;
         let mut line = 1;
 
+        // Skip the shebang line so scripts can be executed directly
+        if self.internal.starts_with("#!") {
+            while iter.next_if(|v| v != &'\n').is_some() {}
+        }
+
         while let Some(value) = iter.next() {
             let token_len = 1;
             col += token_len;
@@ -109,7 +114,10 @@ impl Lexer {
                         || ((c == '-' || c == '+') && iter.peek().is_some_and(|c| c.is_numeric()))
                     {
                         let mut number = c.to_string();
-                        while iter.peek().is_some_and(|v| v.is_alphanumeric()) {
+                        while iter
+                            .peek()
+                            .is_some_and(|v| v.is_alphanumeric() || v == &'.')
+                        {
                             let letter = iter.next().unwrap();
                             col += 1;
                             number.push(letter);
@@ -1,7 +1,5 @@
-#![feature(generic_arg_infer)]
 #![feature(macro_metavar_expr)]
-#![feature(allocator_api)]
-#![feature(test)]
+#![cfg_attr(test, feature(test))]
 
 pub mod ast;
 pub mod interpreter;
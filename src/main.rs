@@ -1,13 +1,18 @@
+use std::{
+    io::{stdin, Read},
+    process::ExitCode,
+};
+
 use alc_lisp::{
     ast::{Node, AST},
     interpreter::{objects::Object, Env, Program},
     lexer::Lexer,
-    native::NATIVE_ENV,
+    native::{set_script_args, time_seed, NATIVE_BUILTINS, NATIVE_ENV},
     repl::{start_repl, ReplArgs},
     utils::timer::Timer,
 };
 use anyhow::Context;
-use clap::{arg, Parser, Subcommand};
+use clap::{Parser, Subcommand};
 
 use colored::Colorize;
 #[cfg(feature = "mimalloc")]
@@ -20,7 +25,13 @@ static GLOBAL: MiMalloc = MiMalloc;
 #[derive(Parser, Debug)]
 #[command(version)]
 struct Args {
+    ///File to run. `-` reads the program from stdin
     file_name: Option<String>,
+
+    ///Read the program from stdin
+    #[arg(long, default_value_t = false, conflicts_with = "file_name")]
+    stdin: bool,
+
     ///Time the execution of the program
     #[arg(short, long, default_value_t = false)]
     time: bool,
@@ -37,6 +48,22 @@ struct Args {
     #[arg(long, default_value_t = false)]
     debug_ast: bool,
 
+    ///Seed of the random number generator. defaults to the current time
+    #[arg(long)]
+    seed: Option<u64>,
+
+    ///Disable the builtins that run other programs, `exec` and `spawn`. Files and environment variables stay available
+    #[arg(long, default_value_t = false)]
+    sandbox: bool,
+
+    ///Start without the functions of the prelude, only the builtins written in rust
+    #[arg(long, default_value_t = false)]
+    no_prelude: bool,
+
+    ///Arguments passed into the script, available as `argv`
+    #[arg(last = true)]
+    args: Vec<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -47,18 +74,27 @@ enum Commands {
     Repl(ReplArgs),
 }
 
-fn main() -> anyhow::Result<()> {
+fn main() -> anyhow::Result<ExitCode> {
     let args = Args::parse();
 
     match (&args.command, &args.file_name) {
-        (Some(Commands::Repl(args)), _) => start_repl(args),
-        (_, None) => start_repl(&ReplArgs::default()),
+        (Some(Commands::Repl(args)), _) => start_repl(args).map(|_| ExitCode::SUCCESS),
+        (_, None) if !args.stdin => start_repl(&ReplArgs::default()).map(|_| ExitCode::SUCCESS),
         _ => run_file(args),
     }
 }
 
-fn run_file(args: Args) -> anyhow::Result<()> {
-    let file = std::fs::read_to_string(args.file_name.unwrap()).context("to open file:")?;
+fn run_file(args: Args) -> anyhow::Result<ExitCode> {
+    let file = match args.file_name.as_deref() {
+        Some("-") | None => {
+            let mut file = String::new();
+            stdin()
+                .read_to_string(&mut file)
+                .context("to read program from stdin:")?;
+            file
+        }
+        Some(file_name) => std::fs::read_to_string(file_name).context("to open file:")?,
+    };
 
     let _t: Timer;
     if args.time {
@@ -100,9 +136,19 @@ fn run_file(args: Args) -> anyhow::Result<()> {
         ast.print_errors(&root);
     }
 
-    let globals: Env = NATIVE_ENV.clone();
+    let mut globals: Env = if args.no_prelude {
+        NATIVE_BUILTINS.clone()
+    } else {
+        NATIVE_ENV.clone()
+    };
+    set_script_args(&mut globals, &args.args);
 
     let mut program = Program::new(globals);
+    program.set_sandboxed(args.sandbox);
+    if let Some(file_name) = args.file_name.as_deref().filter(|name| *name != "-") {
+        program.set_script_path(file_name);
+    }
+    program.set_seed(args.seed.unwrap_or_else(time_seed));
 
     let result = {
         let _t: Timer;
@@ -115,10 +161,45 @@ fn run_file(args: Args) -> anyhow::Result<()> {
     match result.as_ref() {
         Object::Error(err) => {
             println!("{}{}", "error:".red(), err);
+            Ok(ExitCode::FAILURE)
         }
         v => {
             println!("{}", v);
+            Ok(ExitCode::SUCCESS)
         }
     }
-    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alc_lisp::{
+        ast::AST,
+        interpreter::Program,
+        lexer::Lexer,
+        native::{set_script_args, NATIVE_ENV},
+    };
+    use clap::Parser;
+
+    use super::Args;
+
+    #[test]
+    fn arguments_after_the_separator_are_passed_as_argv() {
+        let args =
+            Args::try_parse_from(["alc-lisp", "script.alc", "--", "first", "--time", "-"]).unwrap();
+
+        assert_eq!(args.file_name.as_deref(), Some("script.alc"));
+        assert!(!args.time);
+        assert_eq!(args.args, ["first", "--time", "-"]);
+
+        let mut globals = NATIVE_ENV.clone();
+        set_script_args(&mut globals, &args.args);
+
+        let mut program = Program::new(globals);
+        let mut lexer = Lexer::from_string("[argv]".to_owned());
+        lexer.parse().unwrap();
+        let root = AST::with_tokens(lexer.tokens()).parse().unwrap();
+        let result = program.eval(&root).unwrap();
+
+        assert_eq!(result.to_string(), r#"[["first" "--time" "-"]]"#);
+    }
 }
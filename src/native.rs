@@ -1,15 +1,47 @@
-use std::sync::LazyLock;
+use std::{
+    sync::LazyLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::interpreter::{
     builtins::{add_generic_builtins, native::add_native_builtins},
-    Env,
+    objects::Object,
+    prelude::add_prelude,
+    Env, Reference,
 };
 
-pub static NATIVE_ENV: LazyLock<Env> = LazyLock::new(|| {
+/// Builtins of the binary without the prelude, used with `--no-prelude`
+pub static NATIVE_BUILTINS: LazyLock<Env> = LazyLock::new(|| {
     let mut globals: Env = Env::default();
 
     add_generic_builtins(&mut globals);
     add_native_builtins(&mut globals);
+    set_script_args(&mut globals, &[]);
 
     globals
 });
+
+pub static NATIVE_ENV: LazyLock<Env> = LazyLock::new(|| {
+    let mut globals: Env = NATIVE_BUILTINS.clone();
+
+    add_prelude(&mut globals).expect("the prelude should run");
+
+    globals
+});
+
+/// Exposes the arguments passed into the script as the `argv` list
+pub fn set_script_args(env: &mut Env, args: &[String]) {
+    let argv = args
+        .iter()
+        .map(|arg| Reference::new(Object::String(arg.as_str().into())))
+        .collect();
+
+    env.insert("argv".into(), Reference::new(Object::List(argv)));
+}
+
+/// Returns a seed for the random number generator based on the current time
+pub fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos() as u64)
+}
@@ -1,10 +1,14 @@
 use std::io::{stdin, stdout, Write};
 
 use anyhow::Context;
-use clap::{arg, Parser};
+use clap::Parser;
 use colored::Colorize;
 
-use crate::{interpreter::map_rust_error, native::NATIVE_ENV, utils::timer::Timer};
+use crate::{
+    interpreter::{map_rust_error, objects::Object, Reference},
+    native::{time_seed, NATIVE_BUILTINS, NATIVE_ENV},
+    utils::timer::Timer,
+};
 
 use super::{
     ast::{Node, AST},
@@ -31,6 +35,10 @@ pub struct ReplArgs {
     ///Show AST Debug information
     #[arg(long, default_value_t = false)]
     debug_ast: bool,
+
+    ///Start without the functions of the prelude, only the builtins written in rust
+    #[arg(long, default_value_t = false)]
+    no_prelude: bool,
 }
 
 enum ReplResponse {
@@ -41,7 +49,13 @@ enum ReplResponse {
 pub fn start_repl(repl_args: &ReplArgs) -> anyhow::Result<()> {
     println!("ALC_LISP [{}] REPL - INTERPRETER", VERSION);
 
-    let mut globals = Option::Some(NATIVE_ENV.clone());
+    // a single program keeps the random generator, the selected namespace and the imported modules between lines
+    let mut program = Program::new(if repl_args.no_prelude {
+        NATIVE_BUILTINS.clone()
+    } else {
+        NATIVE_ENV.clone()
+    });
+    program.set_seed(time_seed());
 
     let stdin = stdin();
     let mut stdout = stdout();
@@ -69,45 +83,14 @@ pub fn start_repl(repl_args: &ReplArgs) -> anyhow::Result<()> {
             return Ok(ReplResponse::Continue);
         }
 
-        let mut lexer = Lexer::from_string(line);
-        lexer.parse().context("lexer::parse")?;
-
-        let tokens = lexer.tokens();
-        if repl_args.debug_lexer || repl_args.debug {
-            dbg!(&tokens);
-        }
-
-        let mut ast = AST::with_tokens(tokens);
+        let result = eval_line(&mut program, line, repl_args)?;
 
-        let root: Node;
-        {
-            root = ast.parse().context("ast::parse")?;
-
-            if repl_args.debug_ast || repl_args.debug {
-                dbg!(&root);
-            }
+        println!("{}", result);
 
-            if ast.has_errors() {
-                ast.print_errors(&root);
-            }
+        if let Object::Function { doc: Some(doc), .. } = result.as_ref() {
+            println!("{}", doc.dimmed());
         }
 
-        let taken = globals.take().unwrap();
-        let mut program = Program::new(taken);
-
-        let result = {
-            let _t: Timer;
-            if repl_args.time {
-                _t = Timer::new("EVAL:");
-            }
-            let result = program.eval(&root).context("program::eval");
-            globals = Some(program.get_env().active_slice()[0].read().clone());
-
-            result.and_then(map_rust_error!("eval error"))?
-        };
-
-        println!("{}", result);
-
         Ok(ReplResponse::Continue)
     };
 
@@ -121,3 +104,89 @@ pub fn start_repl(repl_args: &ReplArgs) -> anyhow::Result<()> {
         }
     }
 }
+
+/// Parses and evaluates a line in the program of the session
+fn eval_line(
+    program: &mut Program,
+    line: String,
+    repl_args: &ReplArgs,
+) -> anyhow::Result<Reference> {
+    let mut lexer = Lexer::from_string(line);
+    lexer.parse().context("lexer::parse")?;
+
+    let tokens = lexer.tokens();
+    if repl_args.debug_lexer || repl_args.debug {
+        dbg!(&tokens);
+    }
+
+    let mut ast = AST::with_tokens(tokens);
+
+    let root: Node;
+    {
+        root = ast.parse().context("ast::parse")?;
+
+        if repl_args.debug_ast || repl_args.debug {
+            dbg!(&root);
+        }
+
+        if ast.has_errors() {
+            ast.print_errors(&root);
+        }
+    }
+
+    let _t: Timer;
+    if repl_args.time {
+        _t = Timer::new("EVAL:");
+    }
+
+    program
+        .eval(&root)
+        .context("program::eval")
+        .and_then(map_rust_error!("eval error"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{interpreter::Program, native::NATIVE_ENV};
+
+    use super::{eval_line, ReplArgs};
+
+    fn eval_lines(program: &mut Program, lines: &[&str]) -> String {
+        let args = ReplArgs::default();
+        let mut last = String::new();
+
+        for line in lines {
+            last = eval_line(program, line.to_string(), &args)
+                .unwrap()
+                .to_string();
+        }
+
+        last
+    }
+
+    #[test]
+    fn the_seed_is_kept_between_lines() {
+        let mut program = Program::new(NATIVE_ENV.clone());
+        let first = eval_lines(&mut program, &["(seed 7)", "(random_int 0 1000000)"]);
+        let second = eval_lines(&mut program, &["(seed 7)", "(random_int 0 1000000)"]);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn the_namespace_and_imports_are_kept_between_lines() {
+        let mut program = Program::new(NATIVE_ENV.clone());
+        let result = eval_lines(
+            &mut program,
+            &[
+                "(import \"examples/modules/geometry.alc\")",
+                "(in-ns shapes)",
+                "(def sides 4)",
+                "(in-ns user)",
+                "[shapes/sides sides (geometry/area 2 3)]",
+            ],
+        );
+
+        assert_eq!(result, "[4 null 6]");
+    }
+}
@@ -54,6 +54,7 @@ fn example_tests(fixture: Fixture<&str>) {
     println!("{}", code);
 
     let mut program = new_test_program();
+    program.set_script_path(path);
     let ast = prepare_code((*code).to_owned()).unwrap();
 
     let _last = program.eval(&ast).expect("running code failed");